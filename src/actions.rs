@@ -2,12 +2,14 @@ use crate::board::{piece::PieceKind, Direction, Position};
 use crate::player::Color;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Move {
     pub action: Action,
     pub player: Color,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Action {
     /// Contains the start position and slide direction. The `vec` dictates how many pieces are
     /// carried over to the new field, i.e. if the original stack contains 4 pieces, all of them
@@ -19,3 +21,45 @@ pub enum Action {
     /// target.
     Place(Position, PieceKind),
 }
+
+/// What a player decides to do in their turn.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Decision {
+    /// Performs the action on the board.
+    Play(Action),
+    /// Concedes the game.
+    Resign,
+    /// Offers a draw to the opponent. If they decline, the offering player needs to decide again;
+    /// further offers in the same turn are rejected without using up a retry.
+    OfferDraw,
+}
+
+/// A player's answer to a draw offer.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DrawResponse {
+    Accept,
+    Decline,
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+
+    use super::*;
+    use crate::player::Color;
+    use crate::test_util::check_serde;
+    use serde_json::json;
+
+    #[test]
+    fn test_serde() {
+        let pos = Position::new(0, 1);
+        check_serde(&Action::Place(pos, PieceKind::Stone), json!({ "Place": [{ "row": 0, "col": 1 }, "Stone"] }));
+        check_serde(
+            &Action::Slide(pos, Direction::East, Some(vec![2, 1])),
+            json!({ "Slide": [{ "row": 0, "col": 1 }, "East", [2, 1]] }),
+        );
+        let whole = Action::Slide(pos, Direction::North, None);
+        check_serde(&whole, json!({ "Slide": [{ "row": 0, "col": 1 }, "North", null] }));
+        let mv = Move { action: Action::Place(pos, PieceKind::CapStone), player: Color::Red };
+        check_serde(&mv, json!({ "action": { "Place": [{ "row": 0, "col": 1 }, "CapStone"] }, "player": "Red" }));
+    }
+}
@@ -1,16 +1,17 @@
 use crate::actions::Action;
 use crate::board::piece::PieceKind;
-use crate::board::piece::Stack;
-use crate::board::Board;
-use crate::board::Direction;
-use crate::board::Position;
+use crate::board::{BitBoard, Board, Position};
 use crate::player::Color;
-use std::cmp::{max, min};
+use crate::simulator::logic::movegen;
+use crate::simulator::logic::roads::RoadNetwork;
+use std::cmp::max;
 
 pub struct Analyzer<'a> {
     board: &'a Board,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Metric<T: Default + Copy> {
     red: T,
     blk: T,
@@ -57,6 +58,10 @@ impl<'a> Analyzer<'a> {
         })
     }
 
+    pub fn flats(&self) -> Metric<u16> {
+        Metric::for_values(self.board.flat_count(Color::Red), self.board.flat_count(Color::Blk))
+    }
+
     pub fn stones_left(&self) -> Metric<u16> {
         let red = self.board.piece_count(Color::Red, PieceKind::Stone);
         let blk = self.board.piece_count(Color::Blk, PieceKind::Stone);
@@ -69,6 +74,13 @@ impl<'a> Analyzer<'a> {
         Metric::for_values(red, blk)
     }
 
+    /// The squares of a road of `player` from the South or West edge to the opposite one, if
+    /// they completed a road. See `Logic::winning_road`.
+    pub fn winning_road(&self, player: Color) -> Option<Vec<Position>> {
+        let board = BitBoard::from(self.board);
+        RoadNetwork::build(&board, player).road(&board)
+    }
+
     pub fn highest_stack(&self) -> Metric<usize> {
         self.board.iter().filter(|s| s.is_empty()).fold(Metric::new(), |mut m, s| {
             let v = m.of_mut(s.color().unwrap());
@@ -77,63 +89,9 @@ impl<'a> Analyzer<'a> {
         })
     }
 
+    /// Every placement and slide `player` can perform on the board, assuming the opening is over.
+    /// See `Logic::legal_moves` for details.
     pub fn applicable_actions(&self, player: Color) -> Vec<Action> {
-        type Fields<'a> = Vec<(Position, &'a Stack)>;
-        let (free, occupied): (Fields, Fields) = self.board.iter().with_pos().partition(|(_, stack)| stack.is_empty());
-        let free: Vec<Position> = free.into_iter().map(|(pos, _)| pos).collect();
-
-        let mut actions = Vec::new();
-
-        if self.board.piece_count(player, PieceKind::Stone) > 0 {
-            actions.extend(free.iter().map(|pos| Action::Place(*pos, PieceKind::Stone)));
-            actions.extend(free.iter().map(|pos| Action::Place(*pos, PieceKind::StandingStone)));
-        }
-        if self.board.piece_count(player, PieceKind::CapStone) > 0 {
-            actions.extend(free.iter().map(|pos| Action::Place(*pos, PieceKind::CapStone)));
-        }
-
-        fn make_slides(src: Position, dir: Direction, stack: &Stack, size: usize) -> Vec<Action> {
-            let space_to_border = match dir {
-                Direction::North => size - 1 - src.row,
-                Direction::South => src.row,
-                Direction::East => size - 1 - src.col,
-                Direction::West => src.col,
-            };
-            fn distribute(pieces: usize, parts: usize) -> Vec<usize> {
-                if parts == 1 {
-                    vec![pieces]
-                } else {
-                    (1..=(pieces - parts))
-                        .flat_map(|taken| {
-                            let mut res = vec![taken];
-                            res.extend(distribute(pieces - taken, parts - 1));
-                            res
-                        })
-                        .collect()
-                }
-            }
-            let effective_size = max(stack.len(), size);
-            (1..effective_size)
-                .flat_map(|pieces| {
-                    (1..min(effective_size, space_to_border)).map(move |parts| distribute(pieces, parts))
-                })
-                .map(|v| Action::Slide(src, dir, Some(v)))
-                .collect()
-        }
-
-        let slides = occupied
-            .into_iter()
-            .filter(|(_, stack)| stack.color().unwrap() == player) // Unwrap safe, only occupied fields.
-            .flat_map(|(pos, stack)| {
-                vec![
-                    make_slides(pos, Direction::North, stack, self.board.size()),
-                    make_slides(pos, Direction::South, stack, self.board.size()),
-                    make_slides(pos, Direction::East, stack, self.board.size()),
-                    make_slides(pos, Direction::West, stack, self.board.size()),
-                ].into_iter().flatten()
-            });
-
-        actions.extend(slides);
-        actions
+        movegen::actions(&BitBoard::from(self.board), player)
     }
 }
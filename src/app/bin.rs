@@ -1,10 +1,11 @@
-use tak_simulator;
 use tak_simulator::player::HumanPlayer;
+use tak_simulator::GameConfig;
 
 fn main() {
     println!("Nice.");
     let red = HumanPlayer::command_line_interface();
     let blk = HumanPlayer::command_line_interface();
-    let sim = tak_simulator::Simulator::new(red, blk, 4);
+    let config = GameConfig { illegal_move_retries: 5, ..GameConfig::standard(4).expect("4x4 is a standard size.") };
+    let mut sim = tak_simulator::Simulator::new(red, blk, config).expect("The standard setup is valid.");
     sim.start();
 }
@@ -1,34 +1,38 @@
-pub(crate) mod piece;
+mod bitboard;
+mod error;
+pub mod piece;
 mod position;
+mod text;
+mod topology;
 
+pub use self::bitboard::BitBoard;
+pub use self::error::BoardError;
 use self::piece::Stack;
-pub use self::position::Position;
+pub use self::position::{Position, PositionError};
+pub use self::text::BoardParseError;
+pub use self::topology::Topology;
 use crate::board::piece::Piece;
 use crate::board::piece::PieceKind;
 use crate::player::Color;
-use std::ops::Index;
+use crate::simulator::config::{ConfigError, GameConfig, MAX_SIZE};
+use std::ops::{Index, Not};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct PiecesStash {
     stones: u16,
     caps: u16,
 }
 
 impl PiecesStash {
-    pub(crate) fn for_board_size(n: usize) -> PiecesStash {
-        let (stones, caps) = match n {
-            3 => (10, 0),
-            4 => (15, 0),
-            5 => (21, 1),
-            6 => (30, 1),
-            8 => (50, 2),
-            n => ((n as f32).powf(1.88) as u16, (n / 4) as u16), // The first rule seems about right, the second one not so much.
-        };
-        PiecesStash { stones, caps }
+    pub(crate) fn for_config(config: &GameConfig) -> PiecesStash {
+        PiecesStash { stones: config.stones, caps: config.capstones }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "text::RawBoard"))]
 pub struct Board {
     board: Vec<Vec<Stack>>,
     red_pieces: PiecesStash,
@@ -53,9 +57,16 @@ impl Board {
         &mut self.board[pos.row][pos.col]
     }
 
-    pub fn new(size: usize) -> Board {
-        let stash = PiecesStash::for_board_size(size);
-        Board { board: vec![vec![Stack::empty(); size]; size], red_pieces: stash, blk_pieces: stash }
+    /// An empty board with the reserves of `config`.
+    /// Fails if the board is larger than `MAX_SIZE`, which is the largest board the game logic
+    /// can represent.
+    pub fn new(config: &GameConfig) -> Result<Board, ConfigError> {
+        if config.size > MAX_SIZE {
+            return Err(ConfigError::UnsupportedSize(config.size));
+        }
+        let stash = PiecesStash::for_config(config);
+        let size = config.size;
+        Ok(Board { board: vec![vec![Stack::empty(); size]; size], red_pieces: stash, blk_pieces: stash })
     }
 
     pub fn valid_pos(&self, pos: Position) -> bool {
@@ -81,16 +92,58 @@ impl Board {
         }
     }
 
+    /// Counts the pieces of `c` on the board that were placed as `kind`, i.e. standing stones
+    /// count as regular stones.
+    pub fn placed_count(&self, c: Color, kind: PieceKind) -> u16 {
+        let capstone = kind == PieceKind::CapStone;
+        let pieces = self.iter().flat_map(Stack::iter);
+        pieces.filter(|p| p.color == c && (p.kind == PieceKind::CapStone) == capstone).count() as u16
+    }
+
+    /// Determines whether `c` has neither stones nor capstones left in their stash.
+    pub fn out_of_pieces(&self, c: Color) -> bool {
+        let stash = match c {
+            Color::Red => self.red_pieces,
+            Color::Blk => self.blk_pieces,
+        };
+        stash.stones == 0 && stash.caps == 0
+    }
+
+    /// Determines whether there is no empty position left on the board.
+    pub fn is_full(&self) -> bool {
+        self.iter().all(|s| !s.is_empty())
+    }
+
+    /// Counts the stacks controlled by `c` that are topped by a flat stone.
+    pub fn flat_count(&self, c: Color) -> u16 {
+        self.iter().filter(|s| s.is_flat() && s.color() == Some(c)).count() as u16
+    }
+
     /// Places `piece` at the specified position.
     /// Panics if the position is invalid, occupied, or the player does not have a
     /// suitable piece left in their stash.
     pub fn place(&mut self, piece: Piece, at: Position) {
-        assert!(self.valid_pos(at));
-        assert!(self[at].is_empty());
+        if let Err(e) = self.try_place(piece, at) {
+            panic!("{}", e)
+        }
+    }
+
+    /// Places `piece` at the specified position or explains why this is not possible.
+    /// The board remains unchanged in the latter case.
+    pub fn try_place(&mut self, piece: Piece, at: Position) -> Result<(), BoardError> {
+        if !self.valid_pos(at) {
+            return Err(BoardError::OutOfBounds(at));
+        }
+        if !self[at].is_empty() {
+            return Err(BoardError::OccupiedSquare(at));
+        }
         let left = self.piece_count_mut(piece.color, piece.kind);
-        assert!(*left > 0);
+        if *left == 0 {
+            return Err(BoardError::NoPiecesLeft(piece.color, piece.kind));
+        }
         *left -= 1;
         *self.mut_pos(at) = Stack::from(piece);
+        Ok(())
     }
 
     /// Slides the `n` topmost pieces from `src` in `to` direction.
@@ -104,16 +157,59 @@ impl Board {
         *self.mut_pos(src.go(to)) += carried;
     }
 
+    /// Slides the `n` topmost pieces from `src` in `to` direction on behalf of `c` or explains
+    /// why this is not possible. The board remains unchanged in the latter case.
+    /// `OutOfBounds` refers to `src` if the step in `to` direction leaves the board.
+    pub fn try_slide(&mut self, c: Color, src: Position, to: Direction, n: usize) -> Result<(), BoardError> {
+        if !self.valid_pos(src) {
+            return Err(BoardError::OutOfBounds(src));
+        }
+        let dst = self.topology().step(src, to).ok_or(BoardError::OutOfBounds(src))?;
+        if n == 0 {
+            return Err(BoardError::EmptyCarry);
+        }
+        let stack = &self[src];
+        if n > stack.len() {
+            return Err(BoardError::CarryExceedsStack { available: stack.len(), requested: n });
+        }
+        if stack.color() != Some(c) {
+            return Err(BoardError::WrongOwner(src));
+        }
+        self[dst].check_compatible(&stack.peek_from_top(n))?;
+        self.slide(src, to, n);
+        Ok(())
+    }
+
     pub fn size(&self) -> usize {
         self.board.len()
     }
 
-    #[cfg(test)]
-    pub(crate) fn set_forcefully(&mut self, pos: Position, stack: Stack) {
+    /// Replaces the stack at `pos` with `stack`, taking its pieces from the players' stashes, or
+    /// explains why this is not possible. The board remains unchanged in the latter case.
+    /// Assumes the position to be empty.
+    pub(crate) fn try_set(&mut self, pos: Position, stack: Stack) -> Result<(), BoardError> {
+        if !self.valid_pos(pos) {
+            return Err(BoardError::OutOfBounds(pos));
+        }
+        let mut stash = (self.red_pieces, self.blk_pieces);
         for piece in stack.iter() {
-            *self.piece_count_mut(piece.color, piece.kind) -= 1;
+            let left = match (piece.color, piece.kind) {
+                (Color::Red, PieceKind::CapStone) => &mut stash.0.caps,
+                (Color::Red, _) => &mut stash.0.stones,
+                (Color::Blk, PieceKind::CapStone) => &mut stash.1.caps,
+                (Color::Blk, _) => &mut stash.1.stones,
+            };
+            *left = left.checked_sub(1).ok_or(BoardError::NoPiecesLeft(piece.color, piece.kind))?;
         }
+        self.red_pieces = stash.0;
+        self.blk_pieces = stash.1;
         *self.mut_pos(pos) = stack;
+        Ok(())
+    }
+
+    #[cfg(test)]
+    pub(crate) fn set_forcefully(&mut self, pos: Position, stack: Stack) {
+        self.try_set(pos, stack).unwrap()
     }
 }
 
@@ -152,13 +248,13 @@ impl<'a> Iterator for BoardIterator<'a> {
 }
 
 impl<'a> PosBoardIterator<'a> {
-    fn new(board: &Board) -> PosBoardIterator {
+    fn new(board: &Board) -> PosBoardIterator<'_> {
         PosBoardIterator { row: 0, col: 0, board }
     }
 }
 
 impl<'a> BoardIterator<'a> {
-    fn new(board: &Board) -> BoardIterator {
+    fn new(board: &Board) -> BoardIterator<'_> {
         BoardIterator { inner: PosBoardIterator::new(board) }
     }
 
@@ -168,15 +264,113 @@ impl<'a> BoardIterator<'a> {
 }
 
 impl Board {
-    pub fn iter(&self) -> BoardIterator {
-        BoardIterator::new(&self)
+    pub fn iter(&self) -> BoardIterator<'_> {
+        BoardIterator::new(self)
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Direction {
     North,
     East,
     South,
     West,
 }
+
+impl Not for Direction {
+    type Output = Direction;
+    fn not(self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::East => Direction::West,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::player::Color::*;
+    use crate::test_util::*;
+
+    fn board() -> Board {
+        Board::new(&GameConfig::standard(3).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_new_too_large() {
+        let config = GameConfig { size: 9, ..GameConfig::standard(8).unwrap() };
+        assert_eq!(Board::new(&config), Err(ConfigError::UnsupportedSize(9)));
+    }
+
+    #[test]
+    fn test_try_place() {
+        let mut board = board();
+        let pos = Position::new(1, 1);
+        assert_eq!(board.try_place(stone(Red), Position::new(3, 0)), Err(BoardError::OutOfBounds(Position::new(3, 0))));
+        assert_eq!(board.try_place(stone(Red), pos), Ok(()));
+        assert_eq!(board.try_place(stone(Blk), pos), Err(BoardError::OccupiedSquare(pos)));
+        let other = Position::new(0, 0);
+        assert_eq!(board.try_place(cap_stone(Blk), other), Err(BoardError::NoPiecesLeft(Blk, PieceKind::CapStone)));
+        assert!(board[other].is_empty());
+        assert_eq!(board.piece_count(Red, PieceKind::Stone), 9);
+    }
+
+    #[test]
+    fn test_try_slide() {
+        let mut board = board();
+        let src = Position::new(0, 0);
+        board.set_forcefully(src, stack_stone_rbr());
+        board.set_forcefully(Position::new(0, 1), single_standing(Blk));
+        let control = board.clone();
+        assert_eq!(board.try_slide(Red, src, Direction::South, 1), Err(BoardError::OutOfBounds(src)));
+        assert_eq!(board.try_slide(Red, src, Direction::North, 0), Err(BoardError::EmptyCarry));
+        let err = BoardError::CarryExceedsStack { available: 3, requested: 4 };
+        assert_eq!(board.try_slide(Red, src, Direction::North, 4), Err(err));
+        assert_eq!(board.try_slide(Blk, src, Direction::North, 1), Err(BoardError::WrongOwner(src)));
+        assert_eq!(board.try_slide(Red, src, Direction::East, 1), Err(BoardError::BlockedByWall));
+        assert_eq!(board, control);
+        assert_eq!(board.try_slide(Red, src, Direction::North, 2), Ok(()));
+        assert_eq!(board[Position::new(1, 0)], Stack::from(vec![stone(Blk), stone(Red)]));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use serde_json::json;
+        check_serde(&Direction::North, json!("North"));
+        // Rows are listed from the bottom, i.e. row 0, each from left to right.
+        let mut board = board();
+        board.place(stone(Red), Position::new(0, 0));
+        let expected = json!({
+            "board": [[[{ "kind": "Stone", "color": "Red" }], [], []], [[], [], []], [[], [], []]],
+            "red_pieces": { "stones": 9, "caps": 0 },
+            "blk_pieces": { "stones": 10, "caps": 0 },
+        });
+        check_serde(&board, expected);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialize_invalid() {
+        use serde_json::json;
+        let parse = |board: serde_json::Value, stones: u16| {
+            let stash = json!({ "stones": stones, "caps": 0 });
+            let value = json!({ "board": board, "red_pieces": stash, "blk_pieces": stash });
+            serde_json::from_value::<Board>(value).map_err(|e| e.to_string())
+        };
+        let row = |n: usize| json!(vec![json!([]); n]);
+        assert!(parse(json!([row(3), row(3), row(3)]), 10).is_ok());
+        let error = |e: BoardParseError| Err(e.to_string());
+        assert_eq!(parse(json!([]), 10), error(BoardParseError::Empty));
+        assert_eq!(parse(json!([row(3), row(2), row(3)]), 10), error(BoardParseError::NotSquare(8)));
+        assert_eq!(parse(json!(vec![row(9); 9]), 10), error(BoardParseError::UnsupportedSize(9)));
+        assert_eq!(parse(json!([row(2), row(2)]), 10), error(BoardParseError::UnsupportedSize(2)));
+        assert_eq!(parse(json!([row(1)]), 10), error(BoardParseError::UnsupportedSize(1)));
+        assert_eq!(parse(json!([row(3), row(3), row(3)]), 65), error(BoardParseError::PieceLimit(Red)));
+    }
+}
@@ -0,0 +1,454 @@
+//! A compact representation of a board for search. Squares are bits of a `u64` with the index
+//! `8 * row + col`, so boards up to 8x8 fit. Each player owns the squares topped by one of their
+//! pieces; walls and capstones on top are marked in separate masks. Stacks are packed into a
+//! height and one bit per piece, set for pieces of `Blk`, starting from the bottom. Only the top
+//! piece of a stack can stand or be a capstone, so the masks suffice to restore every piece.
+
+use super::piece::{Piece, PieceKind, Stack};
+use super::{Board, BoardError, Direction, PiecesStash, Position};
+use crate::player::Color;
+use crate::simulator::config::{ConfigError, GameConfig, MAX_SIZE};
+
+/// The number of squares of the largest board.
+const SQUARES: usize = MAX_SIZE * MAX_SIZE;
+
+/// A board in the compact representation, see the module documentation. Converts losslessly to
+/// and from a `Board` and answers the same queries without allocating. It has a fixed size of
+/// about 1.1 KB, mostly the stack colors, so prefer borrowing it over cloning.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitBoard {
+    size: usize,
+    red: u64,
+    blk: u64,
+    walls: u64,
+    caps: u64,
+    heights: [u8; SQUARES],
+    /// Bit `i` is set if the `i`-th piece from the bottom belongs to `Blk`.
+    colors: [u128; SQUARES],
+    red_pieces: PiecesStash,
+    blk_pieces: PiecesStash,
+}
+
+impl BitBoard {
+    /// Fails if the board is larger than `MAX_SIZE`.
+    pub fn new(config: &GameConfig) -> Result<BitBoard, ConfigError> {
+        if config.size > MAX_SIZE {
+            return Err(ConfigError::UnsupportedSize(config.size));
+        }
+        let stash = PiecesStash::for_config(config);
+        Ok(BitBoard::empty(config.size, stash, stash))
+    }
+
+    fn empty(size: usize, red_pieces: PiecesStash, blk_pieces: PiecesStash) -> BitBoard {
+        assert!(size <= MAX_SIZE, "A bitboard holds at most {0}x{0} squares.", MAX_SIZE);
+        BitBoard {
+            size,
+            red: 0,
+            blk: 0,
+            walls: 0,
+            caps: 0,
+            heights: [0; SQUARES],
+            colors: [0; SQUARES],
+            red_pieces,
+            blk_pieces,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn valid_pos(&self, pos: Position) -> bool {
+        pos.row < self.size && pos.col < self.size
+    }
+
+    /// The bit representing `pos` in the masks.
+    pub fn bit(pos: Position) -> u64 {
+        1 << index(pos)
+    }
+
+    /// The positions of the squares in `mask`, inverting `BitBoard::bit`.
+    pub fn positions(mask: u64) -> impl Iterator<Item = Position> {
+        std::iter::successors(Some(mask), |m| Some(m & m.wrapping_sub(1)))
+            .take_while(|m| *m != 0)
+            .map(|m| m.trailing_zeros() as usize)
+            .map(|ix| Position::new(ix / MAX_SIZE, ix % MAX_SIZE))
+    }
+
+    /// The mask of all squares on the board.
+    pub fn squares(&self) -> u64 {
+        let row = (1u64 << self.size) - 1;
+        (0..self.size).fold(0, |mask, r| mask | row << (MAX_SIZE * r))
+    }
+
+    /// The squares controlled by `c`, i.e. topped by one of their pieces.
+    pub fn owned_by(&self, c: Color) -> u64 {
+        match c {
+            Color::Red => self.red,
+            Color::Blk => self.blk,
+        }
+    }
+
+    /// The squares topped by a standing stone.
+    pub fn walls(&self) -> u64 {
+        self.walls
+    }
+
+    /// The squares topped by a capstone.
+    pub fn caps(&self) -> u64 {
+        self.caps
+    }
+
+    /// The squares holding at least one piece.
+    pub fn occupied(&self) -> u64 {
+        self.red | self.blk
+    }
+
+    /// The squares counting towards a road of `c`.
+    pub fn roads(&self, c: Color) -> u64 {
+        self.owned_by(c) & !self.walls
+    }
+
+    /// The squares counting towards the flat count of `c`.
+    pub fn flats(&self, c: Color) -> u64 {
+        self.owned_by(c) & !self.walls & !self.caps
+    }
+
+    pub fn height(&self, pos: Position) -> usize {
+        usize::from(self.heights[index(pos)])
+    }
+
+    pub fn is_empty(&self, pos: Position) -> bool {
+        self.occupied() & Self::bit(pos) == 0
+    }
+
+    /// The player controlling the stack at `pos`, if any.
+    pub fn color(&self, pos: Position) -> Option<Color> {
+        let bit = Self::bit(pos);
+        if self.red & bit != 0 {
+            Some(Color::Red)
+        } else if self.blk & bit != 0 {
+            Some(Color::Blk)
+        } else {
+            None
+        }
+    }
+
+    /// The player whose road is continued by the stack at `pos`, if any.
+    pub fn road_owner(&self, pos: Position) -> Option<Color> {
+        self.color(pos).filter(|_| self.walls & Self::bit(pos) == 0)
+    }
+
+    pub fn top(&self, pos: Position) -> Option<Piece> {
+        self.color(pos).map(|c| Piece::new(self.top_kind(pos), c))
+    }
+
+    /// The stack at `pos`, unpacked.
+    pub fn stack(&self, pos: Position) -> Stack {
+        let (height, colors) = (self.height(pos), self.colors[index(pos)]);
+        let pieces = (0..height)
+            .map(|i| {
+                let color = if colors >> i & 1 == 0 { Color::Red } else { Color::Blk };
+                let kind = if i + 1 == height { self.top_kind(pos) } else { PieceKind::Stone };
+                Piece::new(kind, color)
+            })
+            .collect::<Vec<Piece>>();
+        Stack::from(pieces)
+    }
+
+    fn top_kind(&self, pos: Position) -> PieceKind {
+        let bit = Self::bit(pos);
+        if self.walls & bit != 0 {
+            PieceKind::StandingStone
+        } else if self.caps & bit != 0 {
+            PieceKind::CapStone
+        } else {
+            PieceKind::Stone
+        }
+    }
+
+    pub fn piece_count(&self, c: Color, kind: PieceKind) -> u16 {
+        let stash = self.stash(c);
+        match kind {
+            PieceKind::CapStone => stash.caps,
+            _ => stash.stones,
+        }
+    }
+
+    /// Determines whether `c` has neither stones nor capstones left in their stash.
+    pub fn out_of_pieces(&self, c: Color) -> bool {
+        let stash = self.stash(c);
+        stash.stones == 0 && stash.caps == 0
+    }
+
+    /// Determines whether there is no empty position left on the board.
+    pub fn is_full(&self) -> bool {
+        self.occupied() == self.squares()
+    }
+
+    /// Counts the stacks controlled by `c` that are topped by a flat stone.
+    pub fn flat_count(&self, c: Color) -> u16 {
+        self.flats(c).count_ones() as u16
+    }
+
+    fn stash(&self, c: Color) -> &PiecesStash {
+        match c {
+            Color::Red => &self.red_pieces,
+            Color::Blk => &self.blk_pieces,
+        }
+    }
+
+    fn piece_count_mut(&mut self, c: Color, kind: PieceKind) -> &mut u16 {
+        let stash = match c {
+            Color::Red => &mut self.red_pieces,
+            Color::Blk => &mut self.blk_pieces,
+        };
+        match kind {
+            PieceKind::CapStone => &mut stash.caps,
+            _ => &mut stash.stones,
+        }
+    }
+
+    /// Explains why `n` pieces topped by a piece of `kind` cannot be dropped onto `dst`, if they
+    /// cannot. See `Stack::check_compatible`.
+    pub fn check_compatible(&self, dst: Position, kind: PieceKind, n: usize) -> Result<(), BoardError> {
+        let bit = Self::bit(dst);
+        if self.caps & bit != 0 {
+            Err(BoardError::BlockedByCapstone)
+        } else if self.walls & bit != 0 && (kind != PieceKind::CapStone || n != 1) {
+            Err(BoardError::BlockedByWall)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Places `piece` at the specified position.
+    /// Panics if the position is invalid, occupied, or the player does not have a
+    /// suitable piece left in their stash.
+    pub fn place(&mut self, piece: Piece, at: Position) {
+        if let Err(e) = self.try_place(piece, at) {
+            panic!("{}", e)
+        }
+    }
+
+    /// Places `piece` at the specified position or explains why this is not possible.
+    /// The board remains unchanged in the latter case.
+    pub fn try_place(&mut self, piece: Piece, at: Position) -> Result<(), BoardError> {
+        if !self.valid_pos(at) {
+            return Err(BoardError::OutOfBounds(at));
+        }
+        if !self.is_empty(at) {
+            return Err(BoardError::OccupiedSquare(at));
+        }
+        let left = self.piece_count_mut(piece.color, piece.kind);
+        if *left == 0 {
+            return Err(BoardError::NoPiecesLeft(piece.color, piece.kind));
+        }
+        *left -= 1;
+        self.put(at, 1, color_bits(piece.color), piece.kind);
+        Ok(())
+    }
+
+    /// Slides the `n` topmost pieces from `src` in `to` direction.
+    /// Panics if:
+    /// * `src` is not valid
+    /// * the position in `to` direction of `src` does not exist
+    /// * there are less than `n` pieces on `src`
+    /// * the target position is occupied by a stack incompatible with the slid pieces.
+    pub fn slide(&mut self, src: Position, to: Direction, n: usize) {
+        let dst = src.go(to);
+        let (height, kind) = (self.height(src), self.top_kind(src));
+        assert!(self.valid_pos(dst) && n <= height, "Cannot slide {} pieces from {} to {}.", n, src, dst);
+        if let Err(e) = self.check_compatible(dst, kind, n) {
+            panic!("{}", e)
+        }
+        let colors = self.colors[index(src)];
+        let rest = height - n;
+        self.put(src, rest, colors & low_bits(rest), PieceKind::Stone);
+        let (below, below_colors) = (self.height(dst), self.colors[index(dst)]);
+        self.put(dst, below + n, below_colors | (colors >> rest) << below, kind);
+    }
+
+    /// Slides the `n` topmost pieces from `src` in `to` direction on behalf of `c` or explains
+    /// why this is not possible. The board remains unchanged in the latter case.
+    /// `OutOfBounds` refers to `src` if the step in `to` direction leaves the board.
+    pub fn try_slide(&mut self, c: Color, src: Position, to: Direction, n: usize) -> Result<(), BoardError> {
+        if !self.valid_pos(src) {
+            return Err(BoardError::OutOfBounds(src));
+        }
+        let dst = self.topology().step(src, to).ok_or(BoardError::OutOfBounds(src))?;
+        if n == 0 {
+            return Err(BoardError::EmptyCarry);
+        }
+        if n > self.height(src) {
+            return Err(BoardError::CarryExceedsStack { available: self.height(src), requested: n });
+        }
+        if self.color(src) != Some(c) {
+            return Err(BoardError::WrongOwner(src));
+        }
+        self.check_compatible(dst, self.top_kind(src), n)?;
+        self.slide(src, to, n);
+        Ok(())
+    }
+
+    /// Removes the lone piece at `at` and returns it to its owner's stash, reverting
+    /// `BitBoard::place`.
+    pub(crate) fn unplace(&mut self, at: Position) {
+        let piece = self.top(at).expect("Only placed pieces can be removed.");
+        *self.piece_count_mut(piece.color, piece.kind) += 1;
+        self.put(at, 0, 0, PieceKind::Stone);
+    }
+
+    /// Turns the flat stone on top of `at` back into a standing stone, reverting a flattening.
+    pub(crate) fn stand_up(&mut self, at: Position) {
+        debug_assert_eq!(self.top_kind(at), PieceKind::Stone);
+        self.walls |= Self::bit(at);
+    }
+
+    /// Replaces the stack at `pos` with `height` pieces of the given colors topped by `kind`.
+    fn put(&mut self, pos: Position, height: usize, colors: u128, kind: PieceKind) {
+        let (ix, bit) = (index(pos), Self::bit(pos));
+        self.heights[ix] = height as u8;
+        self.colors[ix] = colors;
+        self.red &= !bit;
+        self.blk &= !bit;
+        self.walls &= !bit;
+        self.caps &= !bit;
+        if height == 0 {
+            return;
+        }
+        match colors >> (height - 1) & 1 {
+            0 => self.red |= bit,
+            _ => self.blk |= bit,
+        }
+        match kind {
+            PieceKind::Stone => {}
+            PieceKind::StandingStone => self.walls |= bit,
+            PieceKind::CapStone => self.caps |= bit,
+        }
+    }
+}
+
+fn index(pos: Position) -> usize {
+    debug_assert!(pos.row < MAX_SIZE && pos.col < MAX_SIZE);
+    MAX_SIZE * pos.row + pos.col
+}
+
+fn low_bits(n: usize) -> u128 {
+    match n {
+        0 => 0,
+        n => !0 >> (128 - n),
+    }
+}
+
+fn color_bits(c: Color) -> u128 {
+    match c {
+        Color::Red => 0,
+        Color::Blk => 1,
+    }
+}
+
+impl From<&Board> for BitBoard {
+    /// Panics if a stack holds more than 128 pieces.
+    fn from(board: &Board) -> BitBoard {
+        let mut bits = BitBoard::empty(board.size(), board.red_pieces, board.blk_pieces);
+        for (pos, stack) in board.iter().with_pos() {
+            assert!(stack.len() <= 128, "The stack at {} is too high for a bitboard.", pos);
+            let colors = stack.iter().rev().fold(0, |bits, p| bits << 1 | color_bits(p.color));
+            bits.put(pos, stack.len(), colors, stack.top().map(|p| p.kind).unwrap_or(PieceKind::Stone));
+        }
+        bits
+    }
+}
+
+impl From<&BitBoard> for Board {
+    fn from(bits: &BitBoard) -> Board {
+        let board = (0..bits.size).map(|row| (0..bits.size).map(|col| bits.stack(Position::new(row, col))).collect());
+        Board { board: board.collect(), red_pieces: bits.red_pieces, blk_pieces: bits.blk_pieces }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::player::Color::*;
+    use crate::test_util::*;
+
+    const MIXED: &str = "! ! ! ! !\n! ! ! ! !\nRS BSRS ! ! !\n! RX ! ! !\nbs ! rsbsrc ! !";
+
+    fn boards() -> Vec<Board> {
+        let texts = [
+            "! ! ! ! ! ! ! ! !",
+            MIXED,
+            "RS   BSRS ! ! !\n!    RX   ! ! !\nBC   !    ! ! !\n!    !    ! ! !\nRSBS !    ! ! RC",
+        ];
+        let mut boards: Vec<Board> = texts.iter().map(|s| s.parse().unwrap()).collect();
+        let mut large = Board::new(&GameConfig::standard(8).unwrap()).unwrap();
+        large.place(cap_stone(Red), Position::new(7, 7));
+        large.place(standing(Blk), Position::new(0, 7));
+        large.place(stone(Red), Position::new(7, 0));
+        boards.push(large);
+        boards
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for board in boards() {
+            let bits = BitBoard::from(&board);
+            assert_eq!(Board::from(&bits), board);
+            for (pos, stack) in board.iter().with_pos() {
+                assert_eq!(bits.stack(pos), *stack);
+                assert_eq!(bits.top(pos), stack.top().cloned());
+                assert_eq!(bits.height(pos), stack.len());
+                assert_eq!(bits.road_owner(pos), stack.color().filter(|_| stack.is_road()));
+            }
+            for c in &[Red, Blk] {
+                assert_eq!(bits.flat_count(*c), board.flat_count(*c));
+                assert_eq!(bits.out_of_pieces(*c), board.out_of_pieces(*c));
+                for kind in &[PieceKind::Stone, PieceKind::CapStone] {
+                    assert_eq!(bits.piece_count(*c, *kind), board.piece_count(*c, *kind));
+                }
+            }
+            assert_eq!(bits.is_full(), board.is_full());
+        }
+    }
+
+    #[test]
+    fn test_masks() {
+        let bits = BitBoard::from(&MIXED.parse::<Board>().unwrap());
+        let mask = |squares: &[&str]| squares.iter().map(|s| BitBoard::bit(s.parse().unwrap())).sum::<u64>();
+        assert_eq!(bits.owned_by(Red), mask(&["a3", "b3", "b2", "c1"]));
+        assert_eq!(bits.owned_by(Blk), mask(&["a1"]));
+        assert_eq!(bits.walls(), mask(&["b2"]));
+        assert_eq!(bits.caps(), mask(&["c1"]));
+        assert_eq!(bits.roads(Red), mask(&["a3", "b3", "c1"]));
+        assert_eq!(bits.flats(Red), mask(&["a3", "b3"]));
+        assert_eq!(bits.squares().count_ones(), 25);
+    }
+
+    #[test]
+    fn test_mutations_match_board() {
+        let mut board: Board = MIXED.parse().unwrap();
+        let mut bits = BitBoard::from(&board);
+        let c1 = Position::new(0, 2);
+        assert_eq!(bits.try_slide(Red, Position::new(2, 1), Direction::South, 2), Err(BoardError::BlockedByWall));
+        assert_eq!(bits.try_slide(Red, c1, Direction::South, 1), Err(BoardError::OutOfBounds(c1)));
+        assert_eq!(bits.try_slide(Blk, c1, Direction::West, 1), Err(BoardError::WrongOwner(c1)));
+        assert_eq!(bits, BitBoard::from(&board));
+
+        bits.try_slide(Red, c1, Direction::West, 3).unwrap();
+        board.try_slide(Red, c1, Direction::West, 3).unwrap();
+        assert_eq!(Board::from(&bits), board);
+        bits.try_slide(Red, Position::new(0, 1), Direction::North, 1).unwrap();
+        board.try_slide(Red, Position::new(0, 1), Direction::North, 1).unwrap();
+        assert_eq!(Board::from(&bits), board);
+        assert_eq!(bits.walls(), 0);
+        assert_eq!(bits.try_place(stone(Blk), c1), Ok(()));
+        assert_eq!(bits.try_place(stone(Blk), c1), Err(BoardError::OccupiedSquare(c1)));
+        bits.unplace(c1);
+        bits.slide(Position::new(1, 1), Direction::South, 1);
+        bits.stand_up(Position::new(1, 1));
+        assert_eq!(bits.top(Position::new(1, 1)), Some(standing(Red)));
+    }
+}
@@ -0,0 +1,46 @@
+use crate::board::piece::PieceKind;
+use crate::board::Position;
+use crate::player::Color;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// The reason why a mutation of a `Board` or `Stack` was refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoardError {
+    /// The position does not exist on the board.
+    OutOfBounds(Position),
+    /// A piece cannot be placed on an occupied position.
+    OccupiedSquare(Position),
+    /// The player has no piece of this kind left in their stash.
+    NoPiecesLeft(Color, PieceKind),
+    /// A slide step needs to carry at least one piece.
+    EmptyCarry,
+    /// The stack holds fewer pieces than requested.
+    CarryExceedsStack { available: usize, requested: usize },
+    /// Only a lone capstone may be dropped onto a standing stone.
+    BlockedByWall,
+    /// Nothing may be dropped onto a capstone.
+    BlockedByCapstone,
+    /// The stack at this position is not controlled by the moving player.
+    WrongOwner(Position),
+}
+
+impl Display for BoardError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            BoardError::OutOfBounds(pos) => write!(f, "{} is not on the board.", pos),
+            BoardError::OccupiedSquare(pos) => write!(f, "{} is already occupied.", pos),
+            BoardError::NoPiecesLeft(c, kind) => write!(f, "{} has no {:?} left in their stash.", c, kind),
+            BoardError::EmptyCarry => write!(f, "A slide needs to carry at least one piece."),
+            BoardError::CarryExceedsStack { available, requested } => {
+                write!(f, "Cannot carry {} pieces from a stack of {}.", requested, available)
+            }
+            BoardError::BlockedByWall => write!(f, "Only a lone capstone can flatten a standing stone."),
+            BoardError::BlockedByCapstone => write!(f, "Nothing can be stacked onto a capstone."),
+            BoardError::WrongOwner(pos) => {
+                write!(f, "The stack at {} belongs to the opponent.", pos)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BoardError {}
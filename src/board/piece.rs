@@ -1,19 +1,24 @@
+use crate::board::BoardError;
+#[cfg(feature = "serde")]
+use crate::board::BoardParseError;
 use crate::player::Color;
 use std::ops::{AddAssign, SubAssign};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Piece {
     pub kind: PieceKind,
     pub color: Color,
 }
 
 impl Piece {
-    pub(crate) fn new(kind: PieceKind, color: Color) -> Piece {
+    pub fn new(kind: PieceKind, color: Color) -> Piece {
         Piece { kind, color }
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PieceKind {
     Stone,
     CapStone,
@@ -46,7 +51,9 @@ impl PieceKind {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Default)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "RawStack", into = "RawStack"))]
 pub struct Stack {
     // The rightmost piece is the top piece.
     content: Vec<Piece>,
@@ -57,7 +64,7 @@ impl Stack {
         self.content[self.len() - 1 - n]
     }
 
-    pub fn iter(&self) -> std::slice::Iter<Piece> {
+    pub fn iter(&self) -> std::slice::Iter<'_, Piece> {
         self.content.iter()
     }
 
@@ -73,6 +80,12 @@ impl Stack {
         self.content.is_empty()
     }
 
+    /// Determines whether the top piece is a flat stone, i.e. whether the stack counts towards
+    /// its owner's flat count.
+    pub fn is_flat(&self) -> bool {
+        self.top().map(|p| p.kind == PieceKind::Stone).unwrap_or(false)
+    }
+
     pub fn compatible_with(&self, other: &Stack) -> bool {
         self.is_stackable() || self.is_flattenable() && other.is_flattening()
     }
@@ -85,6 +98,34 @@ impl Stack {
         self.content.split_off(self.content.len() - n).into()
     }
 
+    /// Removes the `n` topmost pieces or fails if the stack holds fewer than `n` pieces.
+    pub fn try_take_off(&mut self, n: usize) -> Result<Stack, BoardError> {
+        if n > self.len() {
+            return Err(BoardError::CarryExceedsStack { available: self.len(), requested: n });
+        }
+        Ok(self.take_off(n))
+    }
+
+    /// Explains why `other` cannot be stacked onto `self`, if it cannot.
+    pub fn check_compatible(&self, other: &Stack) -> Result<(), BoardError> {
+        if self.compatible_with(other) {
+            Ok(())
+        } else if self.top().map(|p| p.kind == PieceKind::CapStone).unwrap_or(false) {
+            Err(BoardError::BlockedByCapstone)
+        } else {
+            Err(BoardError::BlockedByWall)
+        }
+    }
+
+    /// Stacks `other` onto `self`, flattening a standing stone if necessary.
+    /// Leaves `self` unchanged if the stacks are incompatible.
+    pub fn try_add(&mut self, mut other: Stack) -> Result<(), BoardError> {
+        self.check_compatible(&other)?;
+        self.flatten();
+        self.content.append(&mut other.content);
+        Ok(())
+    }
+
     pub fn empty() -> Stack {
         Stack { content: Vec::new() }
     }
@@ -134,16 +175,16 @@ impl Stack {
         }
     }
 
-    fn top(&self) -> Option<&Piece> {
+    pub fn top(&self) -> Option<&Piece> {
         self.content.last()
     }
 }
 
 impl AddAssign for Stack {
-    fn add_assign(&mut self, mut other: Stack) {
-        assert!(self.compatible_with(&other));
-        self.flatten();
-        self.content.extend(other.content.drain(..))
+    fn add_assign(&mut self, other: Stack) {
+        if let Err(e) = self.try_add(other) {
+            panic!("{}", e)
+        }
     }
 }
 
@@ -173,11 +214,40 @@ impl From<Piece> for Stack {
     }
 }
 
+/// The pieces of a `Stack` as serialized, checked by `Stack::try_from` before use.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+struct RawStack(Vec<Piece>);
+
+#[cfg(feature = "serde")]
+impl From<Stack> for RawStack {
+    fn from(stack: Stack) -> RawStack {
+        RawStack(stack.content)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl std::convert::TryFrom<RawStack> for Stack {
+    type Error = BoardParseError;
+
+    /// Fails like `Stack::from_str` if a piece other than the top one stands or is a capstone.
+    fn try_from(raw: RawStack) -> Result<Stack, BoardParseError> {
+        let stack = Stack { content: raw.0 };
+        if stack.valid() {
+            Ok(stack)
+        } else {
+            Err(BoardParseError::CoveredPiece(stack.to_string()))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::{Piece, Stack};
     use crate::board::piece::PieceKind;
+    use crate::board::BoardError;
     use crate::player::{
         Color,
         Color::{Blk, Red},
@@ -271,4 +341,38 @@ mod tests {
         assert_eq!(s0.nth_piece(6), stone(Red));
     }
 
+    #[test]
+    fn test_try_take_off() {
+        let mut subject = stack_stone_rbr();
+        let err = BoardError::CarryExceedsStack { available: 3, requested: 4 };
+        assert_eq!(subject.try_take_off(4), Err(err));
+        assert_eq!(subject, stack_stone_rbr());
+        assert_eq!(subject.try_take_off(3), Ok(stack_stone_rbr()));
+    }
+
+    #[test]
+    fn test_try_add() {
+        let mut wall = stack_with_standing_rbr();
+        assert_eq!(wall.try_add(single_stone(Blk)), Err(BoardError::BlockedByWall));
+        assert_eq!(wall, stack_with_standing_rbr());
+        let mut cap = stack_with_cap_rbr();
+        assert_eq!(cap.try_add(single_cap(Blk)), Err(BoardError::BlockedByCapstone));
+        assert_eq!(cap, stack_with_cap_rbr());
+        assert_eq!(wall.try_add(single_cap(Blk)), Ok(()));
+        assert_eq!(wall.len(), 4);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use serde_json::json;
+        check_serde(&PieceKind::StandingStone, json!("StandingStone"));
+        check_serde(&Piece::new(PieceKind::CapStone, Blk), json!({ "kind": "CapStone", "color": "Blk" }));
+        // Stacks are listed from the bottom to the top.
+        let stack = Stack::from(vec![stone(Red), standing(Blk)]);
+        check_serde(&stack, json!([{ "kind": "Stone", "color": "Red" }, { "kind": "StandingStone", "color": "Blk" }]));
+        check_serde(&Stack::empty(), json!([]));
+        let covered = json!([{ "kind": "CapStone", "color": "Red" }, { "kind": "Stone", "color": "Blk" }]);
+        assert!(serde_json::from_value::<Stack>(covered).is_err());
+    }
 }
@@ -1,6 +1,12 @@
 use crate::board::Direction;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
 
+/// A square of the board. Row 0 is the bottom row, column 0 the leftmost column.
+/// Positions are written in algebraic notation, i.e. the column as a file letter starting at `a`
+/// followed by the row as a rank starting at `1`: `Position::new(1, 2)` is `c2`.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Position {
     pub row: usize,
     pub col: usize,
@@ -11,6 +17,8 @@ impl Position {
         Position { row, col }
     }
 
+    /// The position one step in `dir` direction. Assumes the step not to leave the board, see
+    /// `Board::step` for a checked version.
     pub fn go(self, dir: Direction) -> Position {
         match dir {
             Direction::North => Position { row: self.row + 1, col: self.col },
@@ -19,4 +27,122 @@ impl Position {
             Direction::West => Position { row: self.row, col: self.col - 1 },
         }
     }
+
+    /// The position one step in `dir` direction, or `None` if it would have a negative coordinate.
+    /// Does not know about the board size, see `Board::step` for that.
+    pub fn checked_go(self, dir: Direction) -> Option<Position> {
+        match dir {
+            Direction::North => Some(Position::new(self.row + 1, self.col)),
+            Direction::East => Some(Position::new(self.row, self.col + 1)),
+            Direction::South => self.row.checked_sub(1).map(|row| Position::new(row, self.col)),
+            Direction::West => self.col.checked_sub(1).map(|col| Position::new(self.row, col)),
+        }
+    }
+
+    /// Parses algebraic coordinates like `c2` and checks that they lie on a board of width `size`.
+    pub fn parse_within(s: &str, size: usize) -> Result<Position, PositionError> {
+        let pos: Position = s.parse()?;
+        if pos.row >= size || pos.col >= size {
+            return Err(PositionError::OutOfBounds { pos, size });
+        }
+        Ok(pos)
+    }
+
+    /// Splits algebraic coordinates off the start of `s` and returns them along with the rest.
+    pub(crate) fn split_algebraic(s: &str) -> Option<(Position, &str)> {
+        let col = match s.chars().next() {
+            Some(c) if c.is_ascii_lowercase() => (c as u8 - b'a') as usize,
+            _ => return None,
+        };
+        let end = s[1..].find(|c: char| !c.is_ascii_digit()).map(|i| i + 1).unwrap_or(s.len());
+        let row = s[1..end].parse::<usize>().ok().filter(|r| *r > 0)?;
+        Some((Position::new(row - 1, col), &s[end..]))
+    }
+}
+
+impl FromStr for Position {
+    type Err = PositionError;
+
+    /// Parses algebraic coordinates like `c2`, ignoring the case of the file letter.
+    fn from_str(s: &str) -> Result<Position, PositionError> {
+        let lower = s.trim().to_lowercase();
+        match Position::split_algebraic(&lower) {
+            Some((pos, "")) => Ok(pos),
+            _ => Err(PositionError::Malformed(s.to_string())),
+        }
+    }
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "{}{}", (b'a' + self.col as u8) as char, self.row + 1)
+    }
+}
+
+/// The reason why a string does not denote a position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PositionError {
+    /// The contained string is not a square like `c2`.
+    Malformed(String),
+    /// The position is not on a board of width `size`.
+    OutOfBounds { pos: Position, size: usize },
+}
+
+impl Display for PositionError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            PositionError::Malformed(s) => write!(f, "'{}' is not a square like 'c2'.", s),
+            PositionError::OutOfBounds { pos, size } => write!(f, "{} is not on a board of size {}.", pos, size),
+        }
+    }
+}
+
+impl std::error::Error for PositionError {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Position::new(0, 0).to_string(), "a1");
+        assert_eq!(Position::new(1, 2).to_string(), "c2");
+        assert_eq!(Position::new(7, 7).to_string(), "h8");
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("a1".parse(), Ok(Position::new(0, 0)));
+        assert_eq!(" C2 ".parse(), Ok(Position::new(1, 2)));
+        assert_eq!("e12".parse(), Ok(Position::new(11, 4)));
+        for s in &["", "a", "a0", "1a", "a1b", "(1, 3)", "!1"] {
+            assert_eq!(s.parse::<Position>(), Err(PositionError::Malformed(s.to_string())));
+        }
+    }
+
+    #[test]
+    fn test_checked_go() {
+        assert_eq!(Position::new(0, 0).checked_go(Direction::South), None);
+        assert_eq!(Position::new(0, 0).checked_go(Direction::West), None);
+        assert_eq!(Position::new(3, 0).checked_go(Direction::North), Some(Position::new(4, 0)));
+        assert_eq!(Position::new(3, 1).checked_go(Direction::West), Some(Position::new(3, 0)));
+    }
+
+    #[test]
+    fn test_parse_within() {
+        assert_eq!(Position::parse_within("e5", 5), Ok(Position::new(4, 4)));
+        let pos = Position::new(0, 5);
+        assert_eq!(Position::parse_within("f1", 5), Err(PositionError::OutOfBounds { pos, size: 5 }));
+        assert_eq!(
+            Position::parse_within("a6", 5),
+            Err(PositionError::OutOfBounds { pos: Position::new(5, 0), size: 5 })
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        crate::test_util::check_serde(&Position::new(1, 2), serde_json::json!({ "row": 1, "col": 2 }));
+    }
 }
@@ -0,0 +1,249 @@
+//! A compact text diagram of a board, e.g.
+//! ```text
+//! RS   BSRS !
+//! !    RX   !
+//! BC   !    RS
+//! ```
+//! Rows are listed from the top of the board, i.e. the row with the highest index. Each square is
+//! either `!` for an empty square or a stack listed from the bottom, each piece being a color
+//! (`R` or `B`) followed by its kind (`S` for a flat stone, `X` or `W` for a standing stone, `C`
+//! for a capstone). Letters are case insensitive and squares are separated by whitespace; the
+//! board is square, so its size follows from the number of squares. The pieces on the board are
+//! taken from the standard stashes for its size, so only sizes with a standard setup are accepted.
+
+use super::piece::{Piece, PieceKind, Stack};
+#[cfg(feature = "serde")]
+use super::PiecesStash;
+use super::{Board, BoardError, Position};
+use crate::player::Color;
+use crate::simulator::config::{GameConfig, MAX_PIECES, MAX_SIZE, MIN_SIZE};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+
+impl FromStr for Board {
+    type Err = BoardParseError;
+
+    fn from_str(s: &str) -> Result<Board, BoardParseError> {
+        let squares: Vec<&str> = s.split_whitespace().collect();
+        let size = (1..=squares.len()).find(|n| n * n >= squares.len()).ok_or(BoardParseError::Empty)?;
+        if size * size != squares.len() {
+            return Err(BoardParseError::NotSquare(squares.len()));
+        }
+        let config = GameConfig::standard(size).map_err(|_| BoardParseError::UnsupportedSize(size))?;
+        let mut board = Board::new(&config).expect("Standard setups fit on a board.");
+        for (i, square) in squares.into_iter().enumerate() {
+            let pos = Position::new(size - 1 - i / size, i % size);
+            board.try_set(pos, parse_stack(square)?).map_err(|e| match e {
+                BoardError::NoPiecesLeft(c, kind) => BoardParseError::TooManyPieces(c, kind),
+                _ => unreachable!("Only the stashes can be exceeded."),
+            })?;
+        }
+        Ok(board)
+    }
+}
+
+/// The fields of a `Board` as serialized, checked by `Board::try_from` before use.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+pub(super) struct RawBoard {
+    board: Vec<Vec<Stack>>,
+    red_pieces: PiecesStash,
+    blk_pieces: PiecesStash,
+}
+
+#[cfg(feature = "serde")]
+impl std::convert::TryFrom<RawBoard> for Board {
+    type Error = BoardParseError;
+
+    /// Fails like `Board::from_str` if the board is not square or its size is outside of
+    /// `MIN_SIZE..=MAX_SIZE`, and if a player has more than `MAX_PIECES` pieces on the board and
+    /// in their stash.
+    fn try_from(raw: RawBoard) -> Result<Board, BoardParseError> {
+        let size = raw.board.len();
+        if size == 0 {
+            return Err(BoardParseError::Empty);
+        }
+        if raw.board.iter().any(|row| row.len() != size) {
+            return Err(BoardParseError::NotSquare(raw.board.iter().map(Vec::len).sum()));
+        }
+        if !(MIN_SIZE..=MAX_SIZE).contains(&size) {
+            return Err(BoardParseError::UnsupportedSize(size));
+        }
+        for &(c, stash) in &[(Color::Red, raw.red_pieces), (Color::Blk, raw.blk_pieces)] {
+            let placed = raw.board.iter().flatten().flat_map(Stack::iter).filter(|p| p.color == c).count();
+            if placed + usize::from(stash.stones) + usize::from(stash.caps) > usize::from(MAX_PIECES) {
+                return Err(BoardParseError::PieceLimit(c));
+            }
+        }
+        Ok(Board { board: raw.board, red_pieces: raw.red_pieces, blk_pieces: raw.blk_pieces })
+    }
+}
+
+impl FromStr for Stack {
+    type Err = BoardParseError;
+
+    /// Parses a single square of the diagram, e.g. `!` or `RSBC`.
+    fn from_str(s: &str) -> Result<Stack, BoardParseError> {
+        parse_stack(s)
+    }
+}
+
+fn parse_stack(s: &str) -> Result<Stack, BoardParseError> {
+    if s == "!" {
+        return Ok(Stack::empty());
+    }
+    let mut pieces = Vec::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        let color = match c.to_ascii_lowercase() {
+            'r' => Color::Red,
+            'b' => Color::Blk,
+            _ => return Err(BoardParseError::InvalidColor(c, s.to_string())),
+        };
+        let kind = match chars.next().map(|k| k.to_ascii_lowercase()) {
+            Some('s') => PieceKind::Stone,
+            Some('x') | Some('w') => PieceKind::StandingStone,
+            Some('c') => PieceKind::CapStone,
+            Some(k) => return Err(BoardParseError::InvalidKind(k, s.to_string())),
+            None => return Err(BoardParseError::IncompletePiece(s.to_string())),
+        };
+        pieces.push(Piece::new(kind, color));
+    }
+    if pieces.iter().rev().skip(1).any(|p| p.kind != PieceKind::Stone) {
+        return Err(BoardParseError::CoveredPiece(s.to_string()));
+    }
+    Ok(Stack::from(pieces))
+}
+
+impl Display for Stack {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        if self.is_empty() {
+            return write!(f, "!");
+        }
+        for piece in self.iter() {
+            let color = match piece.color {
+                Color::Red => 'R',
+                Color::Blk => 'B',
+            };
+            let kind = match piece.kind {
+                PieceKind::Stone => 'S',
+                PieceKind::StandingStone => 'X',
+                PieceKind::CapStone => 'C',
+            };
+            write!(f, "{}{}", color, kind)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Board {
+    /// Writes the diagram with the squares aligned in columns.
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let size = self.size();
+        let squares: Vec<String> =
+            (0..size * size).map(|i| self[Position::new(size - 1 - i / size, i % size)].to_string()).collect();
+        let widths: Vec<usize> =
+            (0..size).map(|col| squares.iter().skip(col).step_by(size).map(String::len).max().unwrap_or(0)).collect();
+        for (i, row) in squares.chunks(size).enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            let row: Vec<String> = row.iter().zip(&widths).map(|(s, w)| format!("{:<w$}", s, w = w)).collect();
+            write!(f, "{}", row.join(" ").trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+/// The reason why a string is not a valid board diagram.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoardParseError {
+    /// The diagram contains no squares.
+    Empty,
+    /// The number of squares is not a square number.
+    NotSquare(usize),
+    /// The board size is outside of `MIN_SIZE..=MAX_SIZE`.
+    UnsupportedSize(usize),
+    /// The character is not a color in the contained square.
+    InvalidColor(char, String),
+    /// The character is not a piece kind in the contained square.
+    InvalidKind(char, String),
+    /// The last piece of the contained square lacks its kind.
+    IncompletePiece(String),
+    /// Only the top piece of the contained square may be a standing stone or capstone.
+    CoveredPiece(String),
+    /// The board holds more pieces of this kind than the player's stash.
+    TooManyPieces(Color, PieceKind),
+    /// The player has more than `MAX_PIECES` pieces on the board and in their stash.
+    PieceLimit(Color),
+}
+
+impl Display for BoardParseError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            BoardParseError::Empty => write!(f, "The board has no squares."),
+            BoardParseError::NotSquare(n) => write!(f, "{} squares do not form a square board.", n),
+            BoardParseError::UnsupportedSize(n) => {
+                write!(f, "Board size {} is not supported, use {} to {}.", n, MIN_SIZE, MAX_SIZE)
+            }
+            BoardParseError::InvalidColor(c, s) => write!(f, "'{}' in '{}' is not a color, use R or B.", c, s),
+            BoardParseError::InvalidKind(c, s) => write!(f, "'{}' in '{}' is not a piece, use S, X, W or C.", c, s),
+            BoardParseError::IncompletePiece(s) => write!(f, "The last piece in '{}' lacks its kind.", s),
+            BoardParseError::CoveredPiece(s) => write!(f, "Only the top piece in '{}' may stand or be a capstone.", s),
+            BoardParseError::TooManyPieces(c, kind) => {
+                write!(f, "{} does not have enough {:?} for this board.", c, kind)
+            }
+            BoardParseError::PieceLimit(c) => write!(f, "{} has more than {} pieces.", c, MAX_PIECES),
+        }
+    }
+}
+
+impl std::error::Error for BoardParseError {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::test_util::*;
+
+    #[test]
+    fn test_parse() {
+        let board: Board = "rs bsrs ! \n ! RX ! \n bs ! rsbs".parse().unwrap();
+        assert_eq!(board.size(), 3);
+        assert_eq!(board[Position::new(2, 0)], single_stone(Color::Red));
+        assert_eq!(board[Position::new(2, 1)], Stack::from(vec![stone(Color::Blk), stone(Color::Red)]));
+        assert_eq!(board[Position::new(1, 1)], single_standing(Color::Red));
+        assert_eq!(board[Position::new(0, 2)], Stack::from(vec![stone(Color::Red), stone(Color::Blk)]));
+        assert!(board[Position::new(0, 1)].is_empty());
+        assert_eq!(board.piece_count(Color::Red, PieceKind::Stone), 10 - 4);
+        assert_eq!(board.piece_count(Color::Blk, PieceKind::Stone), 10 - 3);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let text = "RS   BSRS ! ! !\n\
+                    !    RX   ! ! !\n\
+                    BC   !    ! ! !\n\
+                    !    !    ! ! !\n\
+                    RSBS !    ! ! RC";
+        let board: Board = text.parse().unwrap();
+        assert_eq!(board.to_string(), text);
+        assert_eq!(board.to_string().parse::<Board>().unwrap(), board);
+        let empty = Board::new(&GameConfig::standard(3).unwrap()).unwrap();
+        assert_eq!(empty.to_string(), "! ! !\n! ! !\n! ! !");
+    }
+
+    #[test]
+    fn test_errors() {
+        let parse = |s: &str| s.parse::<Board>();
+        assert_eq!(parse("  "), Err(BoardParseError::Empty));
+        assert_eq!(parse("! ! ! !  !"), Err(BoardParseError::NotSquare(5)));
+        assert_eq!(parse(&["!"; 81].join(" ")), Err(BoardParseError::UnsupportedSize(9)));
+        assert_eq!(parse("! ! ! !"), Err(BoardParseError::UnsupportedSize(2)));
+        assert_eq!(parse("! ! ! ! ! ! ! ! GS"), Err(BoardParseError::InvalidColor('G', "GS".to_string())));
+        assert_eq!(parse("! ! ! ! ! ! ! ! RSBQ"), Err(BoardParseError::InvalidKind('q', "RSBQ".to_string())));
+        assert_eq!(parse("! ! ! ! ! ! ! ! RSB"), Err(BoardParseError::IncompletePiece("RSB".to_string())));
+        assert_eq!(parse("! ! ! ! ! ! ! ! RXBS"), Err(BoardParseError::CoveredPiece("RXBS".to_string())));
+        assert_eq!(parse("! ! ! ! RC ! ! ! !"), Err(BoardParseError::TooManyPieces(Color::Red, PieceKind::CapStone)));
+    }
+}
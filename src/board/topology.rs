@@ -0,0 +1,182 @@
+//! Board-aware navigation: stepping that stays on the board, neighbours, edges and lines of
+//! squares. Positions outside of the board are never produced.
+
+use super::piece::Stack;
+use super::{BitBoard, Board, Direction, Position};
+
+impl Direction {
+    /// All directions in clockwise order, starting with `North`.
+    pub const ALL: [Direction; 4] = [Direction::North, Direction::East, Direction::South, Direction::West];
+}
+
+/// The navigation on a square board, which only depends on its size. Obtained via
+/// `Board::topology` or `BitBoard::topology`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Topology {
+    size: usize,
+}
+
+impl Topology {
+    pub fn new(size: usize) -> Topology {
+        Topology { size }
+    }
+
+    pub fn size(self) -> usize {
+        self.size
+    }
+
+    /// Whether `pos` is on the board.
+    pub fn contains(self, pos: Position) -> bool {
+        pos.row < self.size && pos.col < self.size
+    }
+
+    /// The position one step in `dir` direction from `pos`, if both are on the board.
+    pub fn step(self, pos: Position, dir: Direction) -> Option<Position> {
+        Some(pos).filter(|p| self.contains(*p)).and_then(|p| p.checked_go(dir)).filter(|p| self.contains(*p))
+    }
+
+    /// The positions adjacent to `pos` on the board, in the order of `Direction::ALL`.
+    pub fn neighbours(self, pos: Position) -> impl Iterator<Item = Position> {
+        Direction::ALL.iter().filter_map(move |dir| self.step(pos, *dir))
+    }
+
+    /// The number of steps from `pos` to the border in `dir` direction, i.e. 0 on the edge.
+    /// This and the edge queries below panic if `pos` is not on the board.
+    pub fn distance_to_border(self, pos: Position, dir: Direction) -> usize {
+        assert!(self.contains(pos), "{} is not on the board.", pos);
+        let last = self.size - 1;
+        match dir {
+            Direction::North => last - pos.row,
+            Direction::East => last - pos.col,
+            Direction::South => pos.row,
+            Direction::West => pos.col,
+        }
+    }
+
+    /// Whether `pos` lies on the edge of the board in `dir` direction.
+    pub fn is_on_edge(self, pos: Position, dir: Direction) -> bool {
+        self.distance_to_border(pos, dir) == 0
+    }
+
+    /// Whether `pos` lies on any edge of the board.
+    pub fn is_on_border(self, pos: Position) -> bool {
+        Direction::ALL.iter().any(|dir| self.is_on_edge(pos, *dir))
+    }
+
+    /// The positions on the edge in `dir` direction, e.g. the top row for `North`, from the
+    /// bottom left to the top right.
+    pub fn edge(self, dir: Direction) -> impl Iterator<Item = Position> {
+        let last = self.size - 1;
+        (0..self.size).map(move |ix| match dir {
+            Direction::North => Position::new(last, ix),
+            Direction::East => Position::new(ix, last),
+            Direction::South => Position::new(0, ix),
+            Direction::West => Position::new(ix, 0),
+        })
+    }
+}
+
+impl Board {
+    pub fn topology(&self) -> Topology {
+        Topology::new(self.size())
+    }
+
+    /// The squares of `row` from left to right.
+    pub fn row(&self, row: usize) -> impl Iterator<Item = (Position, &Stack)> {
+        assert!(row < self.size(), "Row {} is not on the board.", row);
+        self.line(Position::new(row, 0), Direction::East)
+    }
+
+    /// The squares of column `col` from bottom to top.
+    pub fn column(&self, col: usize) -> impl Iterator<Item = (Position, &Stack)> {
+        assert!(col < self.size(), "Column {} is not on the board.", col);
+        self.line(Position::new(0, col), Direction::North)
+    }
+
+    /// The squares from `pos` in `dir` direction up to the border, excluding `pos` itself.
+    pub fn ray(&self, pos: Position, dir: Direction) -> impl Iterator<Item = (Position, &Stack)> {
+        self.line(pos, dir).skip(1)
+    }
+
+    /// The squares from `pos` in `dir` direction up to the border, including `pos`.
+    fn line(&self, pos: Position, dir: Direction) -> impl Iterator<Item = (Position, &Stack)> {
+        let topology = self.topology();
+        let len = if topology.contains(pos) { topology.distance_to_border(pos, dir) + 1 } else { 0 };
+        std::iter::successors(Some(pos), move |p| p.checked_go(dir)).take(len).map(move |p| (p, &self[p]))
+    }
+}
+
+impl BitBoard {
+    pub fn topology(&self) -> Topology {
+        Topology::new(self.size())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::simulator::config::GameConfig;
+
+    fn board() -> Board {
+        Board::new(&GameConfig::standard(4).unwrap()).unwrap()
+    }
+
+    fn topology() -> Topology {
+        board().topology()
+    }
+
+    fn positions<'a>(iter: impl Iterator<Item = (Position, &'a Stack)>) -> Vec<String> {
+        iter.map(|(p, _)| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_step() {
+        let topology = topology();
+        assert_eq!(topology.step(Position::new(3, 0), Direction::North), None);
+        assert_eq!(topology.step(Position::new(3, 0), Direction::East), Some(Position::new(3, 1)));
+        assert_eq!(topology.step(Position::new(4, 0), Direction::South), None);
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let topology = topology();
+        let neighbours = |pos| topology.neighbours(pos).map(|p| p.to_string()).collect::<Vec<_>>();
+        assert_eq!(neighbours(Position::new(0, 0)), vec!["a2", "b1"]);
+        assert_eq!(neighbours(Position::new(1, 1)), vec!["b3", "c2", "b1", "a2"]);
+        assert_eq!(neighbours(Position::new(3, 3)), vec!["d3", "c4"]);
+    }
+
+    #[test]
+    fn test_edges() {
+        let topology = topology();
+        assert_eq!(BitBoard::from(&board()).topology(), topology);
+        let pos = Position::new(0, 2);
+        assert_eq!(topology.distance_to_border(pos, Direction::North), 3);
+        assert_eq!(topology.distance_to_border(pos, Direction::East), 1);
+        assert_eq!(topology.distance_to_border(pos, Direction::West), 2);
+        assert!(topology.is_on_edge(pos, Direction::South));
+        assert!(!topology.is_on_edge(pos, Direction::East));
+        assert!(topology.is_on_border(pos));
+        assert!(!topology.is_on_border(Position::new(2, 1)));
+        let edge: Vec<String> = topology.edge(Direction::East).map(|p| p.to_string()).collect();
+        assert_eq!(edge, vec!["d1", "d2", "d3", "d4"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "e1 is not on the board.")]
+    fn test_distance_off_board() {
+        topology().distance_to_border(Position::new(0, 4), Direction::North);
+    }
+
+    #[test]
+    fn test_lines() {
+        let board = board();
+        assert_eq!(positions(board.row(1)), vec!["a2", "b2", "c2", "d2"]);
+        assert_eq!(positions(board.column(2)), vec!["c1", "c2", "c3", "c4"]);
+        assert_eq!(positions(board.ray(Position::new(1, 2), Direction::West)), vec!["b2", "a2"]);
+        assert_eq!(positions(board.ray(Position::new(1, 2), Direction::North)), vec!["c3", "c4"]);
+        assert_eq!(board.ray(Position::new(3, 2), Direction::North).count(), 0);
+        assert_eq!(board.ray(Position::new(4, 2), Direction::South).count(), 0);
+    }
+}
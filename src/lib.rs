@@ -2,10 +2,20 @@
 extern crate lazy_static;
 
 mod actions;
+mod ptn;
 mod simulator;
 mod test_util;
 
 pub mod analyzer;
 pub mod board;
 pub mod player;
+pub use actions::{Action, Decision, DrawResponse, Move};
+pub use ptn::{PtnError, PtnGame, PtnResult, Tps, TpsError};
+pub use simulator::clock::{Clock, SystemClock};
+pub use simulator::config::{ConfigError, GameConfig, Komi, RoadTieRule};
+pub use simulator::game_over::{MatchResult, Outcome, Termination};
+pub use simulator::illegal_move::IllegalMove;
+pub use simulator::logic::{Logic, Undo};
+pub use simulator::record::{GameRecord, RecordedMove};
+pub use simulator::replay::{Replay, ReplayError};
 pub use simulator::Simulator;
@@ -1,6 +1,8 @@
-use crate::actions::Action;
+use crate::actions::{Action, Decision, DrawResponse};
 use crate::board::{Board, Position};
+use crate::simulator::config::GameConfig;
 use crate::simulator::game_over::Outcome;
+use crate::simulator::illegal_move::IllegalMove;
 use std::fmt::{Display, Formatter, Result as FmtResult};
 use std::io::stdin;
 use std::ops::Not;
@@ -29,24 +31,32 @@ impl HumanPlayer {
 }
 
 impl PlayerBuilder<CommandLineHuman> for HumanPlayer {
-    fn setup(self, board_size: usize, color: Color, first: bool) -> CommandLineHuman {
-        CommandLineHuman::new(self.name, board_size, color, first)
+    fn setup(self, config: &GameConfig, color: Color, first: bool) -> CommandLineHuman {
+        CommandLineHuman::new(self.name, config.size, color, first)
     }
 }
 
 pub trait PlayerBuilder<T: Player>: Sized {
-    fn setup(self, board_size: usize, color: Color, first: bool) -> T;
+    fn setup(self, config: &GameConfig, color: Color, first: bool) -> T;
 }
 
 pub trait Player: Clone {
     fn welcome(&mut self, opponent: &str);
-    fn action_for(&mut self, board: &Board, opponent_action: Option<Action>) -> Action;
+    fn action_for(&mut self, board: &Board, opponent_action: Option<Action>) -> Decision;
     fn first_action(&mut self, board: &Board) -> Position;
+    /// Informs the player that `decision` was rejected; they will be asked again unless they ran
+    /// out of retries. Ignores the rejection by default.
+    fn reject(&mut self, _decision: &Decision, _reason: &IllegalMove) {}
+    /// Asks the player whether they accept the opponent's draw offer. Declines by default.
+    fn respond_to_draw(&mut self, _board: &Board) -> DrawResponse {
+        DrawResponse::Decline
+    }
     fn accept_outcome(&mut self, outcome: &Outcome);
     fn name(&self) -> &String;
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Color {
     Red,
     Blk,
@@ -70,3 +80,17 @@ impl Display for Color {
         }
     }
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+
+    use super::*;
+    use crate::test_util::check_serde;
+    use serde_json::json;
+
+    #[test]
+    fn test_serde() {
+        check_serde(&Color::Red, json!("Red"));
+        check_serde(&Color::Blk, json!("Blk"));
+    }
+}
@@ -1,7 +1,9 @@
 use super::{Color, Player};
-use crate::actions::Action;
+use crate::actions::{Action, Decision, DrawResponse};
+use crate::analyzer::Analyzer;
 use crate::board::{Board, Position};
-use crate::simulator::game_over::{MatchResult, Outcome};
+use crate::simulator::game_over::{MatchResult, Outcome, Termination};
+use crate::simulator::illegal_move::IllegalMove;
 use std::io::stdin;
 
 mod cli_parser;
@@ -16,12 +18,13 @@ pub struct CommandLineHuman {
     color: Color,
     opponent: String,
     first: bool,
+    offered_draw: bool,
 }
 
 impl CommandLineHuman {
     pub(crate) fn new(name: String, board_size: usize, color: Color, first: bool) -> CommandLineHuman {
         let printer = CIBoardPrinter::new(board_size);
-        CommandLineHuman { name, printer, color, first, opponent: String::from("Karen") }
+        CommandLineHuman { name, printer, color, first, opponent: String::from("Karen"), offered_draw: false }
     }
 
     fn read_yes_no() -> bool {
@@ -70,7 +73,11 @@ impl Player for CommandLineHuman {
         self.opponent = String::from(opponent);
     }
 
-    fn action_for(&mut self, board: &Board, opponent_action: Option<Action>) -> Action {
+    fn action_for(&mut self, board: &Board, opponent_action: Option<Action>) -> Decision {
+        if self.offered_draw {
+            println!("{} declined your draw offer.", self.opponent);
+            self.offered_draw = false;
+        }
         if let Some(action) = opponent_action {
             println!("The opponent {}.", action.cl_display());
             println!("Now, the situation is as follows:");
@@ -78,10 +85,12 @@ impl Player for CommandLineHuman {
             println!("The situation is as follows:");
         }
         println!("{}", self.printer.print(board));
-        println!("What do you want to do? (Place/Slide/Move)");
+        println!("What do you want to do? (Place/Slide/Move/Draw/Resign)");
         let mut command = String::new();
         let _ = stdin().read_line(&mut command);
-        self.ask_in_loop(CLIParser::action)
+        let decision = self.ask_in_loop(CLIParser::decision);
+        self.offered_draw = decision == Decision::OfferDraw;
+        decision
     }
 
     fn first_action(&mut self, board: &Board) -> Position {
@@ -93,11 +102,44 @@ impl Player for CommandLineHuman {
             );
         }
         println!("Let's get started. Where do you want to place {}'s first piece?", self.opponent);
-        println!("You can enter positions using cartesiean notation, i.e., (1,3) for row 1, column 3, counting bottom to top, left to right.");
-        self.ask_in_loop(CLIParser::position)
+        println!("You can enter squares in algebraic notation, i.e., c2 for column c and row 2, counting bottom to top, left to right from a1.");
+        let size = board.size();
+        self.ask_in_loop(|s| CLIParser::position(s, size))
+    }
+
+    fn reject(&mut self, _decision: &Decision, reason: &IllegalMove) {
+        self.offered_draw = false;
+        println!("Sorry, you can't do that: {}", reason);
+    }
+
+    fn respond_to_draw(&mut self, board: &Board) -> DrawResponse {
+        println!("{} offers a draw. The situation is as follows:", self.opponent);
+        println!("{}", self.printer.print(board));
+        println!("Do you accept?");
+        if Self::read_yes_no() {
+            DrawResponse::Accept
+        } else {
+            DrawResponse::Decline
+        }
     }
 
     fn accept_outcome(&mut self, outcome: &Outcome) {
+        match outcome.termination {
+            Termination::Forfeit(_) => println!("The game ended because of too many illegal moves."),
+            Termination::Resignation => println!("The game ended by resignation."),
+            Termination::Agreement => println!("The game ended in a draw by agreement."),
+            Termination::Repetition => println!("The game ended because the same position kept recurring."),
+            Termination::Adjudication => println!("The game reached the move limit and was decided by flat count."),
+            Termination::Timeout => println!("The game ended because a player ran out of time."),
+            Termination::Road => {
+                if let MatchResult::Winner(c) = outcome.result {
+                    let road = Analyzer::for_board(&outcome.board).winning_road(c).unwrap_or_default();
+                    let squares: Vec<String> = road.iter().map(|pos| pos.cl_display()).collect();
+                    println!("The game ended with a road along {}.", squares.join(", "));
+                }
+            }
+            Termination::Flats => {}
+        }
         match outcome.result {
             MatchResult::Winner(c) if c == self.color => println!("Congratulations, you won, {}!", self.name),
             MatchResult::Winner(_) => println!("Congratulations, you suck! {} beat you with ease.", self.opponent),
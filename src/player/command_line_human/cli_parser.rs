@@ -1,5 +1,5 @@
-use crate::actions::Action;
-use crate::board::Position;
+use crate::actions::{Action, Decision};
+use crate::board::{Position, PositionError};
 
 use crate::board::piece::PieceKind;
 use crate::board::Direction;
@@ -10,6 +10,17 @@ pub(crate) struct CLIParser {}
 pub(crate) type Result<T> = std::result::Result<T, CLIParserError<T>>;
 
 impl CLIParser {
+    pub(crate) fn decision(s: &str) -> Result<Decision> {
+        let normalized = s.trim().to_lowercase();
+        match normalized.as_str() {
+            "resign" | "give up" | "surrender" => Ok(Decision::Resign),
+            "draw" | "offer draw" | "offer a draw" => Ok(Decision::OfferDraw),
+            _ => Self::action(s)
+                .map(Decision::Play)
+                .map_err(|e| CLIParserError { help: e.help, best_guess: e.best_guess.map(Decision::Play) }),
+        }
+    }
+
     pub(crate) fn action(s: &str) -> Result<Action> {
         let s = s.trim().to_lowercase();
         let tokens: Vec<&str> = s.split_whitespace().collect();
@@ -19,7 +30,10 @@ impl CLIParser {
             match tokens[0] {
                 "place" | "set" => Self::place(&tokens[1..]),
                 "move" | "slide" => Self::slide(&tokens[1..]),
-                _ => Err(CLIParserError::new("Unknown command word. Try `place`, `slide`, or `move`.", None)),
+                _ => Err(CLIParserError::new(
+                    "Unknown command word. Try `place`, `slide`, `move`, `draw`, or `resign`.",
+                    None,
+                )),
             }
         }
     }
@@ -29,44 +43,37 @@ impl CLIParser {
     }
 
     fn exact_slide(tokens: &[&str]) -> Result<Action> {
-        //        lazy_static! {
-        //            static ref regex: Regex = Regex::new(
-        //                r#"(stone | flat | standing\s*stone | wall | cap\s*stone)\s*(to | at | on)?\s*\(\s*(\d+)\s*,\s*(\d+)\s*\)"#
-        //            ).unwrap();
-        //        }
-        let regex = Regex::new(
-            r#"(?:from)?\s*\(\s*(\d+)\s*,\s*(\d+)\s*\)\s*((north|east|west|south)\s*(?:taking|moving)*\s*((\d*\s*,?\s*)*))?"#
-        ).unwrap();
+        lazy_static! {
+            static ref regex: Regex = Regex::new(
+                r#"(?:from)?\s*([a-z]\d+)\s*((north|east|west|south)\s*(?:taking|moving)*\s*((\d*\s*,?\s*)*))?"#
+            )
+            .unwrap();
+        }
 
         // Capture groups:
         // 0: full match
-        // 1: row
-        // 2: col
-        // 3: direction
-        // 4: optional; full match for optional drops
-        // 5: optional; list of carries
-
-        regex
-            .captures_iter(&tokens.join(""))
-            .map(|cap| {
-                let row = cap[1].parse::<usize>().unwrap();
-                let col = cap[2].parse::<usize>().unwrap();
-                let pos = Position::new(row, col);
-                let dir = Self::direction(&cap[3]);
-                if cap.len() > 4 {
-                    let carries = Self::number_list(&cap[5]);
-                    Action::Slide(pos, dir, Some(carries))
-                } else {
-                    Action::Slide(pos, dir, None)
-                }
-            })
-            .next()
-            .map(Ok)
-            .unwrap_or_else(|| Err(CLIParserError::new("I don't understand...", None)))
+        // 1: square
+        // 2: optional; direction and carries
+        // 3: optional; direction
+        // 4: optional; list of carries
+
+        let joined = tokens.join("");
+        let cap = regex.captures(&joined).ok_or_else(|| CLIParserError::new("I don't understand...", None))?;
+        let pos = cap[1].parse::<Position>().map_err(|e| CLIParserError::new(&e.to_string(), None))?;
+        let dir = match cap.get(3) {
+            Some(dir) => Self::direction(dir.as_str()),
+            None => return Err(CLIParserError::new("Which direction? Try `north`, `east`, `south`, or `west`.", None)),
+        };
+        let carries = cap.get(4).map(|m| Self::number_list(m.as_str())).unwrap_or_default();
+        if carries.is_empty() {
+            Ok(Action::Slide(pos, dir, None))
+        } else {
+            Ok(Action::Slide(pos, dir, Some(carries)))
+        }
     }
 
     fn number_list(s: &str) -> Vec<usize> {
-        let regex = Regex::new(r#"\d*"#).unwrap();
+        let regex = Regex::new(r#"\d+"#).unwrap();
         regex.find_iter(s).map(|m| m.as_str().parse::<usize>().unwrap()).collect()
     }
 
@@ -81,18 +88,14 @@ impl CLIParser {
     }
 
     fn place(tokens: &[&str]) -> Result<Action> {
-        if let Some(action) = Self::exact_place(tokens) {
-            Ok(action)
-        } else {
-            unimplemented!()
-        }
+        Self::exact_place(tokens)
+            .ok_or_else(|| CLIParserError::new("I don't understand... Try something like `place stone at c2`.", None))
     }
 
     fn exact_place(tokens: &[&str]) -> Option<Action> {
         lazy_static! {
-            static ref regex: Regex = Regex::new(
-                r#"(stone | flat | standing\s*stone | wall | cap\s*stone)\s*(to | at | on)?\s*\(\s*(\d+)\s*,\s*(\d+)\s*\)"#
-            ).unwrap();
+            static ref regex: Regex =
+                Regex::new(r#"(stone|flat|standing\s*stone|wall|cap\s*stone)\s*(to|at|on)?\s*([a-z]\d+)"#).unwrap();
         }
         regex
             .captures_iter(&tokens.join(""))
@@ -100,12 +103,10 @@ impl CLIParser {
                 // cap[0] is the full match.
                 let kind = Self::piece_kind(&cap[1]);
                 // cap[2] is the optional connective.
-                let row = cap[3].parse::<usize>().unwrap();
-                let col = cap[4].parse::<usize>().unwrap();
-                let pos = Position::new(row, col);
-                Action::Place(pos, kind)
+                cap[3].parse::<Position>().ok().map(|pos| Action::Place(pos, kind))
             })
             .next()
+            .flatten()
     }
 
     fn piece_kind(s: &str) -> PieceKind {
@@ -118,34 +119,27 @@ impl CLIParser {
         }
     }
 
-    pub(crate) fn position(s: &str) -> Result<Position> {
-        if let Some(pos) = Self::exact_position(s) {
-            return Ok(pos);
-        }
+    /// Parses algebraic coordinates like `c2` on a board of width `size`.
+    pub(crate) fn position(s: &str, size: usize) -> Result<Position> {
         let s = s.trim().to_lowercase();
-
-        let numbers = Self::extract_numbers(&s);
-        if numbers.len() == 2 {
-            let best_guess = Some(Position::new(numbers[0], numbers[1]));
-            Err(CLIParserError::new("I'm not sure I understood that correctly.", best_guess))
-        } else {
-            Err(CLIParserError::new("I have no idea what you're talking about.", None))
-        }
-    }
-
-    fn exact_position(s: &str) -> Option<Position> {
-        lazy_static! {
-            static ref regex: Regex = Regex::new(r#"\(\s*\d+\s*,\s*\d+\s*\)"#).expect("Meh");
+        match Position::parse_within(&s, size) {
+            Ok(pos) => Ok(pos),
+            Err(PositionError::OutOfBounds { .. }) => Err(CLIParserError::new("That's not on the board.", None)),
+            Err(PositionError::Malformed(_)) => {
+                // Maybe they used the `(row, col)` notation, counting from 0.
+                let numbers = Self::extract_numbers(&s);
+                let guess = Some(numbers.as_slice())
+                    .filter(|n| n.len() == 2 && n[0] < size && n[1] < size)
+                    .map(|n| Position::new(n[0], n[1]));
+                let help = "Enter squares like c2 for the third column of the second row.";
+                Err(CLIParserError::new(help, guess))
+            }
         }
-        regex
-            .captures_iter(s)
-            .map(|cap| Position::new(cap[1].parse::<usize>().unwrap(), cap[2].parse::<usize>().unwrap()))
-            .next()
     }
 
     fn extract_numbers(s: &str) -> Vec<usize> {
         lazy_static! {
-            static ref regex: Regex = Regex::new(r#"\d*"#).unwrap();
+            static ref regex: Regex = Regex::new(r#"\d+"#).unwrap();
         }
         regex
             .find_iter(s)
@@ -164,3 +158,40 @@ impl<T> CLIParserError<T> {
         CLIParserError { help: String::from(help), best_guess }
     }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn action(s: &str) -> Option<Action> {
+        CLIParser::action(s).ok()
+    }
+
+    #[test]
+    fn test_place() {
+        assert_eq!(action("place stone at c2"), Some(Action::Place(Position::new(1, 2), PieceKind::Stone)));
+        assert_eq!(action("Place wall on A1"), Some(Action::Place(Position::new(0, 0), PieceKind::StandingStone)));
+        assert_eq!(action("set cap stone e5"), Some(Action::Place(Position::new(4, 4), PieceKind::CapStone)));
+        assert_eq!(action("place stone at (1, 2)"), None);
+    }
+
+    #[test]
+    fn test_slide() {
+        let b2 = Position::new(1, 1);
+        assert_eq!(action("move b2 north"), Some(Action::Slide(b2, Direction::North, None)));
+        assert_eq!(
+            action("slide from b2 east taking 3, 1"),
+            Some(Action::Slide(b2, Direction::East, Some(vec![3, 1])))
+        );
+        assert_eq!(action("move b2"), None);
+    }
+
+    #[test]
+    fn test_position() {
+        assert_eq!(CLIParser::position(" B3 ", 5).ok(), Some(Position::new(2, 1)));
+        assert!(CLIParser::position("f1", 5).is_err());
+        let guess = CLIParser::position("(1, 3)", 5).err().and_then(|e| e.best_guess);
+        assert_eq!(guess, Some(Position::new(1, 3)));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::actions::Action;
+use crate::actions::{Action, Decision};
 use crate::board::piece::{Piece, PieceKind, Stack};
 use crate::board::{Board, Direction, Position};
 use crate::player::Color;
@@ -13,7 +13,7 @@ pub(crate) trait CLHumanDisplay {
 impl CLHumanDisplay for Action {
     fn cl_display(&self) -> String {
         match self {
-            Action::Place(pos, kind) => format!("placed a {} at {}", pos.cl_display(), kind.cl_display()),
+            Action::Place(pos, kind) => format!("placed a {} at {}", kind.cl_display(), pos.cl_display()),
             Action::Slide(src, dir, Some(v)) if v.len() == 1 => {
                 format!("moved {} pieces from {} {}", v[0], src.cl_display(), dir.cl_display())
             }
@@ -26,6 +26,16 @@ impl CLHumanDisplay for Action {
     }
 }
 
+impl CLHumanDisplay for Decision {
+    fn cl_display(&self) -> String {
+        match self {
+            Decision::Play(action) => action.cl_display(),
+            Decision::Resign => String::from("resign"),
+            Decision::OfferDraw => String::from("offer a draw"),
+        }
+    }
+}
+
 impl CLHumanDisplay for PieceKind {
     fn cl_display(&self) -> String {
         match self {
@@ -38,8 +48,7 @@ impl CLHumanDisplay for PieceKind {
 
 impl CLHumanDisplay for Position {
     fn cl_display(&self) -> String {
-        let c: u8 = 65u8 + (self.col as u8);
-        format!("({}, {})", self.row, c as char)
+        self.to_string()
     }
 }
 
@@ -119,7 +128,7 @@ impl BoardCell {
 
     fn get_row(&self, i: usize) -> String {
         if i < self.inner.len() {
-            self.inner[i].iter().fold(String::new(), |s1, s2| s1 + &s2)
+            self.inner[i].iter().fold(String::new(), |s1, s2| s1 + s2)
         } else {
             String::new() // We're already fully printed.
         }
@@ -177,11 +186,11 @@ impl CIBoardPrinter {
     }
 
     fn inner_range(&self) -> Range<usize> {
-        (1..(self.size - 1)) // exclude border/header/footer
+        1..(self.size - 1) // exclude border/header/footer
     }
 
     fn full_range(&self) -> Range<usize> {
-        (0..self.size)
+        0..self.size
     }
 
     fn inner_to_pos(&self, row: usize, col: usize) -> Position {
@@ -255,10 +264,11 @@ mod tests {
     use crate::board::Board;
     use crate::board::Position;
     use crate::player::Color;
+    use crate::simulator::config::GameConfig;
     use colored::*;
 
     fn setup(size: usize) -> (Board, CIBoardPrinter) {
-        (Board::new(size), CIBoardPrinter::new(size))
+        (Board::new(&GameConfig { size, ..GameConfig::standard(3).unwrap() }).unwrap(), CIBoardPrinter::new(size))
     }
 
     fn bold(s: &str) -> String {
@@ -269,14 +279,14 @@ mod tests {
     fn test_empty() {
         let (board, printer) = setup(2);
         let was = printer.print(&board);
-        let expected = vec![
+        let expected = [
             bold("┼------┼"),
-            bold("|") + &"      " + &bold("|"),
-            bold("|") + &"      " + &bold("|"),
-            bold("|") + &"      " + &bold("|"),
-            bold("|") + &"      " + &bold("|"),
-            bold("|") + &"      " + &bold("|"),
-            bold("|") + &"      " + &bold("|"),
+            bold("|") + "      " + &bold("|"),
+            bold("|") + "      " + &bold("|"),
+            bold("|") + "      " + &bold("|"),
+            bold("|") + "      " + &bold("|"),
+            bold("|") + "      " + &bold("|"),
+            bold("|") + "      " + &bold("|"),
             bold("┼------┼"),
         ]
         .join("\n");
@@ -290,14 +300,14 @@ mod tests {
         board.set_forcefully(Position::new(1, 1), stack);
         println!("{}", printer.print(&board));
         let was = printer.print(&board);
-        let expected = vec![
+        let expected = [
             bold("┼------┼"),
-            bold("|") + &"      " + &bold("|"),
-            bold("|") + &"      " + &bold("|"),
-            bold("|") + &"      " + &bold("|"),
-            bold("|") + &"      " + &bold("|"),
-            bold("|") + &"      " + &bold("|"),
-            bold("|") + &"      " + &bold("|"),
+            bold("|") + "      " + &bold("|"),
+            bold("|") + "      " + &bold("|"),
+            bold("|") + "      " + &bold("|"),
+            bold("|") + "      " + &bold("|"),
+            bold("|") + "      " + &bold("|"),
+            bold("|") + "      " + &bold("|"),
             bold("┼------┼"),
         ]
         .join("\n");
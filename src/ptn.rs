@@ -0,0 +1,289 @@
+//! Conversions between the crate's types and Portable Tak Notation (PTN).
+//! Squares are written in the algebraic notation of `Position`, e.g. `a1` is `Position::new(0, 0)`.
+
+mod game;
+mod tps;
+
+pub use self::game::{PtnGame, PtnResult};
+pub use self::tps::{Tps, TpsError};
+
+use crate::actions::Action;
+use crate::board::piece::PieceKind;
+use crate::board::{Board, Direction, Position};
+use crate::simulator::illegal_move::IllegalMove;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+
+/// The reason why a string is not valid PTN.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PtnError {
+    /// The move string is empty.
+    Empty,
+    /// The contained part is not a square like `c3`.
+    InvalidSquare(String),
+    /// A slide needs to pick up at least one piece.
+    ZeroCount,
+    /// The contained move names a piece count but no direction to slide in.
+    MissingDirection(String),
+    /// Only placements may name a piece kind, e.g. `Sa1`.
+    KindOnSlide(char),
+    /// The contained drop counts are not a sequence of positive digits.
+    InvalidDrops(String),
+    /// The drop counts of a slide need to add up to the number of picked up pieces.
+    DropCountMismatch { picked_up: usize, dropped: usize },
+    /// The contained characters follow an otherwise complete move.
+    TrailingCharacters(String),
+    /// The contained line is not a tag like `[Size "5"]`.
+    MalformedTag(String),
+    /// A game needs a `Size` tag.
+    MissingSize,
+    /// The value of the tag is not supported.
+    InvalidTag { tag: String, value: String },
+    /// The contained string is not a result like `R-0` or `1/2-1/2`.
+    InvalidResult(String),
+    /// A comment lacks its closing brace.
+    UnclosedComment,
+    /// The moves are not numbered consecutively.
+    UnexpectedMoveNumber { expected: usize, found: String },
+    /// The move with this number is malformed.
+    InvalidMove { number: usize, error: Box<PtnError> },
+    /// The move with this number is illegal in the game so far.
+    IllegalMove { number: usize, ptn: String, reason: IllegalMove },
+    /// The move with this number follows the end of the game.
+    MoveAfterEnd { number: usize },
+}
+
+impl Display for PtnError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            PtnError::Empty => write!(f, "The move is empty."),
+            PtnError::InvalidSquare(s) => write!(f, "'{}' is not a square like 'c3'.", s),
+            PtnError::ZeroCount => write!(f, "A slide needs to pick up at least one piece."),
+            PtnError::MissingDirection(s) => write!(f, "'{}' needs a direction, one of '+', '-', '>' or '<'.", s),
+            PtnError::KindOnSlide(c) => write!(f, "Slides cannot name a piece kind, found '{}'.", c),
+            PtnError::InvalidDrops(s) => write!(f, "'{}' is not a sequence of positive drop counts.", s),
+            PtnError::DropCountMismatch { picked_up, dropped } => {
+                write!(f, "The slide picks up {} pieces but drops {}.", picked_up, dropped)
+            }
+            PtnError::TrailingCharacters(s) => write!(f, "Unexpected '{}' after the move.", s),
+            PtnError::MalformedTag(s) => write!(f, "'{}' is not a tag like '[Size \"5\"]'.", s),
+            PtnError::MissingSize => write!(f, "The game lacks a Size tag."),
+            PtnError::InvalidTag { tag, value } => write!(f, "'{}' is not a valid {}.", value, tag),
+            PtnError::InvalidResult(s) => write!(f, "'{}' is not a result like 'R-0' or '1/2-1/2'.", s),
+            PtnError::UnclosedComment => write!(f, "A comment is missing its closing brace."),
+            PtnError::UnexpectedMoveNumber { expected, found } => {
+                write!(f, "Expected move number {}. but found '{}'.", expected, found)
+            }
+            PtnError::InvalidMove { number, error } => write!(f, "Move {}: {}", number, error),
+            PtnError::IllegalMove { number, ptn, reason } => {
+                write!(f, "Move {} ({}) is illegal: {}", number, ptn, reason)
+            }
+            PtnError::MoveAfterEnd { number } => write!(f, "Move {} follows the end of the game.", number),
+        }
+    }
+}
+
+impl std::error::Error for PtnError {}
+
+/// Characters PTN allows after a move to annotate it, e.g. `'` for Tak or `*` for a flattened wall.
+const ANNOTATIONS: &[char] = &['\'', '"', '!', '?', '*'];
+
+impl FromStr for Action {
+    type Err = PtnError;
+
+    /// Parses a PTN move like `a1`, `Sb3`, `Cc2`, `3c3>111` or `c4-`.
+    /// Slides always carry an explicit list of carries, i.e. `Some(carries)`.
+    fn from_str(s: &str) -> Result<Action, PtnError> {
+        let s = s.trim().trim_end_matches(ANNOTATIONS);
+        let first = s.chars().next().ok_or(PtnError::Empty)?;
+        let kind = match first {
+            'F' => Some(PieceKind::Stone),
+            'S' => Some(PieceKind::StandingStone),
+            'C' => Some(PieceKind::CapStone),
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            let (pos, rest) = parse_square(&s[1..])?;
+            return match rest.chars().next() {
+                None => Ok(Action::Place(pos, kind)),
+                Some(c) if direction(c).is_some() => Err(PtnError::KindOnSlide(first)),
+                Some(_) => Err(PtnError::TrailingCharacters(rest.to_string())),
+            };
+        }
+
+        let digits = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let count = match &s[..digits] {
+            "" => None,
+            n => Some(n.parse::<usize>().map_err(|_| PtnError::InvalidSquare(s.to_string()))?),
+        };
+        if count == Some(0) {
+            return Err(PtnError::ZeroCount);
+        }
+        let (pos, rest) = parse_square(&s[digits..])?;
+        let mut rest = rest.chars();
+        let dir = match rest.next() {
+            None if count.is_none() => return Ok(Action::Place(pos, PieceKind::Stone)),
+            None => return Err(PtnError::MissingDirection(s.to_string())),
+            Some(c) => direction(c).ok_or_else(|| PtnError::TrailingCharacters(rest_of(c, &rest)))?,
+        };
+        let drops = rest.as_str();
+        let count = count.unwrap_or(1);
+        let drops: Vec<usize> = match drops {
+            "" => vec![count],
+            _ => drops
+                .chars()
+                .map(|c| c.to_digit(10).filter(|d| *d > 0).map(|d| d as usize))
+                .collect::<Option<_>>()
+                .ok_or_else(|| PtnError::InvalidDrops(drops.to_string()))?,
+        };
+        let dropped = drops.iter().sum();
+        if dropped != count {
+            return Err(PtnError::DropCountMismatch { picked_up: count, dropped });
+        }
+        let carries = drops
+            .iter()
+            .scan(count, |carried, drop| {
+                let carry = *carried;
+                *carried -= drop;
+                Some(carry)
+            })
+            .collect();
+        Ok(Action::Slide(pos, dir, Some(carries)))
+    }
+}
+
+impl Action {
+    /// Writes the action in its shortest PTN form, omitting a count of one and the drop count if
+    /// all pieces are dropped on a single square. The height of the moved stack is taken from
+    /// `board` if the slide carries the entire stack.
+    pub fn to_ptn(&self, board: &Board) -> String {
+        self.ptn_with(|pos| board[pos].len())
+    }
+
+    /// Writes the action like `Action::to_ptn`, asking `height` for the height of the moved stack
+    /// if the slide carries the entire stack.
+    pub(crate) fn ptn_with(&self, height: impl FnOnce(Position) -> usize) -> String {
+        match self {
+            Action::Place(pos, PieceKind::Stone) => pos.to_string(),
+            Action::Place(pos, PieceKind::StandingStone) => format!("S{}", pos),
+            Action::Place(pos, PieceKind::CapStone) => format!("C{}", pos),
+            Action::Slide(pos, dir, None) => slide_ptn(*pos, *dir, &[height(*pos)]),
+            Action::Slide(pos, dir, Some(carries)) => slide_ptn(*pos, *dir, carries),
+        }
+    }
+}
+
+fn slide_ptn(pos: Position, dir: Direction, carries: &[usize]) -> String {
+    let mut res = String::new();
+    if let Some(n) = carries.first().filter(|n| **n > 1) {
+        res.push_str(&n.to_string());
+    }
+    res.push_str(&format!("{}{}", pos, symbol(dir)));
+    if carries.len() > 1 {
+        for w in carries.windows(2) {
+            res.push_str(&(w[0] - w[1]).to_string());
+        }
+        res.push_str(&carries[carries.len() - 1].to_string());
+    }
+    res
+}
+
+/// Parses the square at the start of `s` and returns the remainder.
+fn parse_square(s: &str) -> Result<(Position, &str), PtnError> {
+    Position::split_algebraic(s).ok_or_else(|| PtnError::InvalidSquare(s.to_string()))
+}
+
+fn rest_of(c: char, rest: &std::str::Chars) -> String {
+    format!("{}{}", c, rest.as_str())
+}
+
+fn direction(c: char) -> Option<Direction> {
+    match c {
+        '+' => Some(Direction::North),
+        '-' => Some(Direction::South),
+        '>' => Some(Direction::East),
+        '<' => Some(Direction::West),
+        _ => None,
+    }
+}
+
+fn symbol(dir: Direction) -> char {
+    match dir {
+        Direction::North => '+',
+        Direction::South => '-',
+        Direction::East => '>',
+        Direction::West => '<',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::simulator::config::GameConfig;
+
+    fn parse(s: &str) -> Result<Action, PtnError> {
+        s.parse()
+    }
+
+    #[test]
+    fn test_parse_placements() {
+        assert_eq!(parse("a1"), Ok(Action::Place(Position::new(0, 0), PieceKind::Stone)));
+        assert_eq!(parse("Fa1"), Ok(Action::Place(Position::new(0, 0), PieceKind::Stone)));
+        assert_eq!(parse("Sb3"), Ok(Action::Place(Position::new(2, 1), PieceKind::StandingStone)));
+        assert_eq!(parse("Cc2"), Ok(Action::Place(Position::new(1, 2), PieceKind::CapStone)));
+        assert_eq!(parse("h8'"), Ok(Action::Place(Position::new(7, 7), PieceKind::Stone)));
+    }
+
+    #[test]
+    fn test_parse_slides() {
+        let c3 = Position::new(2, 2);
+        assert_eq!(parse("3c3>111"), Ok(Action::Slide(c3, Direction::East, Some(vec![3, 2, 1]))));
+        assert_eq!(parse("c4-"), Ok(Action::Slide(Position::new(3, 2), Direction::South, Some(vec![1]))));
+        assert_eq!(parse("5c3+"), Ok(Action::Slide(c3, Direction::North, Some(vec![5]))));
+        assert_eq!(parse("5c3<23*"), Ok(Action::Slide(c3, Direction::West, Some(vec![5, 3]))));
+        assert_eq!(parse("4c3>121"), Ok(Action::Slide(c3, Direction::East, Some(vec![4, 3, 1]))));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse(""), Err(PtnError::Empty));
+        assert_eq!(parse("z"), Err(PtnError::InvalidSquare("z".to_string())));
+        assert_eq!(parse("Xa1"), Err(PtnError::InvalidSquare("Xa1".to_string())));
+        assert_eq!(parse("a0"), Err(PtnError::InvalidSquare("a0".to_string())));
+        assert_eq!(parse("0a1>"), Err(PtnError::ZeroCount));
+        assert_eq!(parse("3a1"), Err(PtnError::MissingDirection("3a1".to_string())));
+        assert_eq!(parse("Sa1>"), Err(PtnError::KindOnSlide('S')));
+        assert_eq!(parse("3a1>102"), Err(PtnError::InvalidDrops("102".to_string())));
+        assert_eq!(parse("3a1>12x"), Err(PtnError::InvalidDrops("12x".to_string())));
+        assert_eq!(parse("3a1>11"), Err(PtnError::DropCountMismatch { picked_up: 3, dropped: 2 }));
+        assert_eq!(parse("a1x"), Err(PtnError::TrailingCharacters("x".to_string())));
+        assert_eq!(parse("Ca1+"), Err(PtnError::KindOnSlide('C')));
+    }
+
+    #[test]
+    fn test_write_round_trip() {
+        let board = Board::new(&GameConfig::standard(8).unwrap()).unwrap();
+        for s in &["a1", "Sb3", "Cc2", "3c3>111", "c4-", "5c3+", "5c3<23", "h8<", "2e5+11"] {
+            assert_eq!(parse(s).unwrap().to_ptn(&board), *s);
+        }
+        assert_eq!(parse("Fa1").unwrap().to_ptn(&board), "a1");
+        assert_eq!(parse("1a1>1").unwrap().to_ptn(&board), "a1>");
+    }
+
+    #[test]
+    fn test_whole_stack_round_trip() {
+        let board: Board = "\
+        !  !      !
+        !  RSBSRS !
+        !  !      !
+        "
+        .parse()
+        .unwrap();
+        let c2 = Position::new(1, 1);
+        let action = Action::Slide(c2, Direction::East, None);
+        let ptn = action.to_ptn(&board);
+        assert_eq!(ptn, "3b2>");
+        assert_eq!(parse(&ptn), Ok(Action::Slide(c2, Direction::East, Some(vec![3]))));
+    }
+}
@@ -0,0 +1,544 @@
+use super::{PtnError, Tps};
+use crate::actions::Move;
+use crate::player::Color;
+use crate::simulator::config::{ConfigError, GameConfig, Komi};
+use crate::simulator::game_over::{MatchResult, Outcome, Termination};
+use crate::simulator::logic::Logic;
+use regex::Regex;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+
+/// A complete game in PTN: the header tags followed by the moves.
+/// Red is `Player1`, i.e. the player making the first move.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PtnGame {
+    pub player1: Option<String>,
+    pub player2: Option<String>,
+    pub date: Option<String>,
+    pub size: usize,
+    pub komi: Komi,
+    /// Regular stones per player, written as the `Flats` tag if it deviates from the standard.
+    pub stones: u16,
+    /// Capstones per player, written as the `Caps` tag if it deviates from the standard.
+    pub capstones: u16,
+    pub result: Option<PtnResult>,
+    /// The position the game started from.
+    pub tps: Option<String>,
+    /// Tags not covered by the fields above, in the order they appeared.
+    pub tags: Vec<(String, String)>,
+    /// The moves of the game. Slides carrying the entire stack are stored with their explicit carry.
+    moves: Vec<Move>,
+    /// Comments along with the number of moves preceding them.
+    pub comments: Vec<(usize, String)>,
+}
+
+impl PtnGame {
+    /// An empty game played with `config`.
+    pub fn new(config: &GameConfig) -> PtnGame {
+        PtnGame {
+            player1: None,
+            player2: None,
+            date: None,
+            size: config.size,
+            komi: config.komi,
+            stones: config.stones,
+            capstones: config.capstones,
+            result: None,
+            tps: None,
+            tags: Vec::new(),
+            moves: Vec::new(),
+            comments: Vec::new(),
+        }
+    }
+
+    /// The record of a finished game between `player1` playing Red and `player2` playing Blk,
+    /// starting on an empty board. Fails at the first move that is illegal or follows the end of
+    /// the game.
+    pub fn from_outcome(
+        config: &GameConfig,
+        player1: &str,
+        player2: &str,
+        outcome: &Outcome,
+    ) -> Result<PtnGame, PtnError> {
+        let mut game = PtnGame {
+            player1: Some(player1.to_string()),
+            player2: Some(player2.to_string()),
+            result: Some(PtnResult::from(outcome)),
+            ..PtnGame::new(config)
+        };
+        game.extend_moves(outcome.moves.iter().cloned())?;
+        Ok(game)
+    }
+
+    /// The moves of the game. Slides always carry an explicit list of carries.
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// Appends `moves` to the game, resolving slides that carry the entire stack against the
+    /// board they are played on. Fails at the first move that is illegal or follows the end of
+    /// the game, in which case no move is appended.
+    pub fn extend_moves<I: IntoIterator<Item = Move>>(&mut self, moves: I) -> Result<(), PtnError> {
+        let (mut logic, mut over) = self.play_moves()?;
+        let mut played = Vec::new();
+        for mv in moves {
+            over = play(&mut logic, over, mv)?;
+            played.push(logic.last_applied_move().expect("A move was just applied."));
+        }
+        self.moves.extend(played);
+        Ok(())
+    }
+
+    /// The configuration described by the header. Rules PTN cannot express are left at their
+    /// defaults. Fails if there is no standard setup for the size.
+    pub fn config(&self) -> Result<GameConfig, ConfigError> {
+        Ok(GameConfig {
+            komi: self.komi,
+            stones: self.stones,
+            capstones: self.capstones,
+            ..GameConfig::standard(self.size)?
+        })
+    }
+
+    /// Replays the moves and returns the final state of the game.
+    /// Fails at the first move that is illegal or follows the end of the game.
+    pub fn replay(&self) -> Result<Logic, PtnError> {
+        self.play_moves().map(|(logic, _)| logic)
+    }
+
+    /// Replays the moves and returns the final state along with whether the game is over.
+    fn play_moves(&self) -> Result<(Logic, bool), PtnError> {
+        let mut logic = self.start()?;
+        let mut over = false;
+        for mv in &self.moves {
+            over = play(&mut logic, over, mv.clone())?;
+        }
+        Ok((logic, over))
+    }
+
+    /// The state of the game before the first move, i.e. at the position described by the `TPS`
+    /// tag if present.
+    pub(crate) fn start(&self) -> Result<Logic, PtnError> {
+        let config = match self.config().and_then(|config| config.validate().map(|()| config)) {
+            Ok(config) => config,
+            Err(ConfigError::NoStones) | Err(ConfigError::TooManyPieces) => {
+                return Err(PtnError::InvalidTag { tag: "Flats".to_string(), value: self.stones.to_string() })
+            }
+            Err(_) => return Err(PtnError::InvalidTag { tag: "Size".to_string(), value: self.size.to_string() }),
+        };
+        match &self.tps {
+            None => Ok(Logic::new(config)),
+            Some(tps) => {
+                let tps = Tps::parse(tps, &config)
+                    .map_err(|_| PtnError::InvalidTag { tag: "TPS".to_string(), value: tps.clone() })?;
+                let ply = tps.ply();
+                Ok(Logic::from_position(config, tps.board, ply))
+            }
+        }
+    }
+
+    /// The number of plies played before the first move.
+    fn start_ply(&self) -> usize {
+        let config = self.config().ok();
+        let tps = self.tps.as_ref().zip(config).and_then(|(tps, config)| Tps::parse(tps, &config).ok());
+        tps.map(|tps| tps.ply()).unwrap_or(0)
+    }
+
+    fn set_tag(&mut self, tag: &str, value: &str) -> Result<(), PtnError> {
+        let invalid = || PtnError::InvalidTag { tag: tag.to_string(), value: value.to_string() };
+        match tag {
+            "Player1" => self.player1 = Some(value.to_string()),
+            "Player2" => self.player2 = Some(value.to_string()),
+            "Date" => self.date = Some(value.to_string()),
+            "Size" => self.size = value.parse().map_err(|_| invalid())?,
+            "Komi" => self.komi = parse_komi(value).ok_or_else(invalid)?,
+            "Flats" => self.stones = value.parse().map_err(|_| invalid())?,
+            "Caps" => self.capstones = value.parse().map_err(|_| invalid())?,
+            "Result" if value.is_empty() => self.result = None,
+            "Result" => self.result = Some(value.parse().map_err(|_| invalid())?),
+            "TPS" => self.tps = Some(value.to_string()),
+            _ => self.tags.push((tag.to_string(), value.to_string())),
+        }
+        Ok(())
+    }
+}
+
+/// Applies `mv` and determines whether it ended the game.
+/// Fails if `mv` is illegal or the game is `over` already.
+fn play(logic: &mut Logic, over: bool, mv: Move) -> Result<bool, PtnError> {
+    let number = move_number(logic.ply());
+    if over {
+        return Err(PtnError::MoveAfterEnd { number });
+    }
+    logic.check(&mv).map_err(|reason| PtnError::IllegalMove {
+        number,
+        ptn: mv.action.ptn_with(|pos| logic.peek().height(pos)),
+        reason,
+    })?;
+    logic.make(mv);
+    Ok(logic.is_over())
+}
+
+/// The PTN move number of the move played in ply `ply`, counting from zero.
+fn move_number(ply: usize) -> usize {
+    ply / 2 + 1
+}
+
+/// Parses komi like `2` or `2.5`.
+fn parse_komi(s: &str) -> Option<Komi> {
+    let (flats, half) = match s.find('.') {
+        Some(i) => match &s[i + 1..] {
+            "0" => (&s[..i], 0),
+            "5" => (&s[..i], 1),
+            _ => return None,
+        },
+        None => (s, 0),
+    };
+    flats.parse::<u16>().ok().map(|flats| Komi::from_half_flats(2 * flats + half))
+}
+
+impl FromStr for PtnGame {
+    type Err = PtnError;
+
+    /// Parses a PTN file and replays it; fails at the first illegal move.
+    fn from_str(s: &str) -> Result<PtnGame, PtnError> {
+        lazy_static! {
+            static ref TAG: Regex = Regex::new(r#"^\[\s*(\w+)\s+"([^"]*)"\s*\]$"#).unwrap();
+            static ref NUMBER: Regex = Regex::new(r#"^(\d+)\.$"#).unwrap();
+        }
+        let mut lines = s.lines().map(str::trim).skip_while(|l| l.is_empty()).peekable();
+        let mut tags = Vec::new();
+        while let Some(line) = lines.next_if(|l| l.is_empty() || l.starts_with('[')) {
+            if !line.is_empty() {
+                let captures = TAG.captures(line).ok_or_else(|| PtnError::MalformedTag(line.to_string()))?;
+                tags.push((captures[1].to_string(), captures[2].to_string()));
+            }
+        }
+        let (_, size) = tags.iter().find(|(tag, _)| tag == "Size").ok_or(PtnError::MissingSize)?;
+        // Start with the standard piece counts of the size so that `Flats` and `Caps` override them.
+        let standard = size.parse().ok().and_then(|n| GameConfig::standard(n).ok());
+        let standard = standard.ok_or_else(|| PtnError::InvalidTag { tag: "Size".to_string(), value: size.clone() })?;
+        let mut game = PtnGame::new(&standard);
+        for (tag, value) in &tags {
+            game.set_tag(tag, value)?;
+        }
+
+        let body: Vec<&str> = lines.collect();
+        let mut logic = game.start()?;
+        let mut over = false;
+        let mut placeholder = false;
+        for token in tokens(&body.join("\n"))? {
+            let number = move_number(logic.ply());
+            match token {
+                Token::Comment(text) => game.comments.push((game.moves.len(), text)),
+                Token::Word(word) => {
+                    // Blk's first move from a position may follow the placeholder `--` for Red's.
+                    let first = game.moves.is_empty() && !placeholder;
+                    if let Some(captures) = NUMBER.captures(word) {
+                        let in_turn = logic.to_move() == Color::Red || first;
+                        if captures[1].parse::<usize>().ok() != Some(number) || !in_turn {
+                            return Err(PtnError::UnexpectedMoveNumber { expected: number, found: word.to_string() });
+                        }
+                    } else if word == "--" && first && logic.to_move() == Color::Blk {
+                        placeholder = true;
+                    } else if let Ok(result) = word.parse::<PtnResult>() {
+                        game.result = Some(result);
+                    } else if over {
+                        return Err(PtnError::MoveAfterEnd { number });
+                    } else {
+                        let mv = logic
+                            .move_from_ptn(word)
+                            .map_err(|error| PtnError::InvalidMove { number, error: Box::new(error) })?;
+                        logic.check(&mv).map_err(|reason| PtnError::IllegalMove {
+                            number,
+                            ptn: word.to_string(),
+                            reason,
+                        })?;
+                        game.moves.push(mv.clone());
+                        if let Some(outcome) = logic.apply(mv) {
+                            over = true;
+                            game.result = game.result.or_else(|| Some(PtnResult::from(&outcome)));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(game)
+    }
+}
+
+enum Token<'a> {
+    Word(&'a str),
+    Comment(String),
+}
+
+/// Splits the body of a PTN file into words and comments in curly braces.
+fn tokens(body: &str) -> Result<Vec<Token<'_>>, PtnError> {
+    let mut res = Vec::new();
+    let mut rest = body;
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            return Ok(res);
+        }
+        if let Some(comment) = rest.strip_prefix('{') {
+            let end = comment.find('}').ok_or(PtnError::UnclosedComment)?;
+            res.push(Token::Comment(comment[..end].trim().to_string()));
+            rest = &comment[end + 1..];
+        } else {
+            let end = rest.find(|c: char| c.is_whitespace() || c == '{').unwrap_or(rest.len());
+            res.push(Token::Word(&rest[..end]));
+            rest = &rest[end..];
+        }
+    }
+}
+
+impl Display for PtnGame {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let standard = GameConfig::standard(self.size).ok();
+        let mut tags = Vec::new();
+        tags.extend(self.player1.iter().map(|p| ("Player1", p.clone())));
+        tags.extend(self.player2.iter().map(|p| ("Player2", p.clone())));
+        tags.extend(self.date.iter().map(|d| ("Date", d.clone())));
+        tags.push(("Size", self.size.to_string()));
+        tags.push(("Komi", self.komi.to_string()));
+        if standard.as_ref().map(|c| c.stones) != Some(self.stones) {
+            tags.push(("Flats", self.stones.to_string()));
+        }
+        if standard.as_ref().map(|c| c.capstones) != Some(self.capstones) {
+            tags.push(("Caps", self.capstones.to_string()));
+        }
+        tags.extend(self.result.iter().map(|r| ("Result", r.to_string())));
+        tags.extend(self.tps.iter().map(|t| ("TPS", t.clone())));
+        tags.extend(self.tags.iter().map(|(t, v)| (t.as_str(), v.clone())));
+        for (tag, value) in tags {
+            writeln!(f, "[{} \"{}\"]", tag, value)?;
+        }
+        writeln!(f)?;
+
+        let comments_after = |n: usize| self.comments.iter().filter(move |(i, _)| *i == n).map(|(_, c)| c);
+        for comment in comments_after(0) {
+            writeln!(f, "{{{}}}", comment)?;
+        }
+        let start = self.start_ply();
+        for (i, mv) in self.moves.iter().enumerate() {
+            let ply = start + i;
+            let action = mv.action.ptn_with(|_| unreachable!("Slides in a PtnGame carry explicit carries."));
+            match ply % 2 {
+                0 => write!(f, "{}. {}", move_number(ply), action)?,
+                _ if i == 0 => write!(f, "{}. -- {}", move_number(ply), action)?,
+                _ => write!(f, " {}", action)?,
+            }
+            for comment in comments_after(i + 1) {
+                write!(f, " {{{}}}", comment)?;
+            }
+            if ply % 2 == 1 || i + 1 == self.moves.len() {
+                writeln!(f)?;
+            }
+        }
+        if let Some(result) = self.result {
+            writeln!(f, "{}", result)?;
+        }
+        Ok(())
+    }
+}
+
+/// The result of a game as written in PTN, e.g. `R-0` for a road win of Red.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PtnResult {
+    Road(Color),
+    Flats(Color),
+    /// A win by other means such as resignation, forfeit or timeout.
+    Other(Color),
+    Draw,
+}
+
+impl PtnResult {
+    pub fn result(self) -> MatchResult {
+        match self {
+            PtnResult::Road(c) | PtnResult::Flats(c) | PtnResult::Other(c) => MatchResult::Winner(c),
+            PtnResult::Draw => MatchResult::Tie,
+        }
+    }
+}
+
+impl From<&Outcome> for PtnResult {
+    fn from(outcome: &Outcome) -> PtnResult {
+        match (outcome.result, &outcome.termination) {
+            (MatchResult::Tie, _) => PtnResult::Draw,
+            (MatchResult::Winner(c), Termination::Road) => PtnResult::Road(c),
+            (MatchResult::Winner(c), Termination::Flats) | (MatchResult::Winner(c), Termination::Adjudication) => {
+                PtnResult::Flats(c)
+            }
+            (MatchResult::Winner(c), _) => PtnResult::Other(c),
+        }
+    }
+}
+
+impl FromStr for PtnResult {
+    type Err = PtnError;
+
+    fn from_str(s: &str) -> Result<PtnResult, PtnError> {
+        match s {
+            "R-0" => Ok(PtnResult::Road(Color::Red)),
+            "0-R" => Ok(PtnResult::Road(Color::Blk)),
+            "F-0" => Ok(PtnResult::Flats(Color::Red)),
+            "0-F" => Ok(PtnResult::Flats(Color::Blk)),
+            "1-0" => Ok(PtnResult::Other(Color::Red)),
+            "0-1" => Ok(PtnResult::Other(Color::Blk)),
+            "1/2-1/2" => Ok(PtnResult::Draw),
+            _ => Err(PtnError::InvalidResult(s.to_string())),
+        }
+    }
+}
+
+impl Display for PtnResult {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let s = match self {
+            PtnResult::Road(Color::Red) => "R-0",
+            PtnResult::Road(Color::Blk) => "0-R",
+            PtnResult::Flats(Color::Red) => "F-0",
+            PtnResult::Flats(Color::Blk) => "0-F",
+            PtnResult::Other(Color::Red) => "1-0",
+            PtnResult::Other(Color::Blk) => "0-1",
+            PtnResult::Draw => "1/2-1/2",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::actions::Action;
+    use crate::board::piece::PieceKind;
+    use crate::board::{Direction, Position};
+    use crate::simulator::illegal_move::IllegalMove;
+
+    const ROAD_GAME: &str = r#"
+[Player1 "Alice"]
+[Player2 "Bob"]
+[Date "2026.10.16"]
+[Size "3"]
+[Komi "0"]
+[Event "Test"]
+
+{Both players open in the corners.}
+1. c3 a1
+2. a2 {Threatening a road.} b2
+3. a3 R-0
+"#;
+
+    #[test]
+    fn test_parse_game() {
+        let game: PtnGame = ROAD_GAME.parse().unwrap();
+        assert_eq!(game.player1.as_deref(), Some("Alice"));
+        assert_eq!(game.player2.as_deref(), Some("Bob"));
+        assert_eq!(game.date.as_deref(), Some("2026.10.16"));
+        assert_eq!(game.size, 3);
+        assert_eq!(game.stones, 10);
+        assert_eq!(game.tags, vec![("Event".to_string(), "Test".to_string())]);
+        assert_eq!(game.result, Some(PtnResult::Road(Color::Red)));
+        assert_eq!(game.moves.len(), 5);
+        assert_eq!(
+            game.moves[0],
+            Move { action: Action::Place(Position::new(2, 2), PieceKind::Stone), player: Color::Blk }
+        );
+        assert_eq!(game.moves[4].player, Color::Red);
+        assert_eq!(
+            game.comments,
+            vec![(0, "Both players open in the corners.".to_string()), (3, "Threatening a road.".to_string())]
+        );
+        assert!(game.replay().is_ok());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let game: PtnGame = ROAD_GAME.parse().unwrap();
+        let written = game.to_string();
+        assert_eq!(written.parse::<PtnGame>().unwrap(), game);
+        assert!(written.contains("1. c3 a1\n2. a2 {Threatening a road.} b2\n3. a3\nR-0\n"));
+    }
+
+    #[test]
+    fn test_results() {
+        for s in &["R-0", "0-R", "F-0", "0-F", "1-0", "0-1", "1/2-1/2"] {
+            assert_eq!(s.parse::<PtnResult>().unwrap().to_string(), *s);
+        }
+        assert_eq!("0-0".parse::<PtnResult>(), Err(PtnError::InvalidResult("0-0".to_string())));
+    }
+
+    #[test]
+    fn test_komi_and_piece_counts() {
+        let game: PtnGame = "[Size \"6\"]\n[Komi \"2.5\"]\n[Caps \"2\"]\n\n1. a1 f6\n".parse().unwrap();
+        assert_eq!(game.komi, Komi::from_half_flats(5));
+        assert_eq!((game.stones, game.capstones), (30, 2));
+        let written = game.to_string();
+        assert!(written.contains("[Komi \"2.5\"]\n[Caps \"2\"]\n"));
+        assert!(!written.contains("Flats"));
+    }
+
+    #[test]
+    fn test_illegal_move() {
+        let ptn = "[Size \"3\"]\n1. a1 c3\n2. a1 b2\n";
+        let reason = IllegalMove::OccupiedSquare(Position::new(0, 0));
+        assert_eq!(ptn.parse::<PtnGame>(), Err(PtnError::IllegalMove { number: 2, ptn: "a1".to_string(), reason }));
+        let ptn = "[Size \"3\"]\n1. a1 c3\n2. Cb2\n";
+        let reason = IllegalMove::NoPiecesLeft(PieceKind::CapStone);
+        assert_eq!(ptn.parse::<PtnGame>(), Err(PtnError::IllegalMove { number: 2, ptn: "Cb2".to_string(), reason }));
+        let ptn = "[Size \"3\"]\n1. c3 a1\n2. a2 b2\n3. a3 b1\n";
+        assert_eq!(ptn.parse::<PtnGame>(), Err(PtnError::MoveAfterEnd { number: 3 }));
+    }
+
+    #[test]
+    fn test_from_position() {
+        let tps = "[TPS \"2,x2/x,1,x/1,x2 2 3\"]\n";
+        let game: PtnGame = format!("[Size \"3\"]\n{}\n3. -- b3\n4. c1 c3\n", tps).parse().unwrap();
+        assert_eq!(game.moves.len(), 3);
+        assert_eq!(
+            game.moves[0],
+            Move { action: Action::Place(Position::new(2, 1), PieceKind::Stone), player: Color::Blk }
+        );
+        assert_eq!(game.result, Some(PtnResult::Road(Color::Blk)));
+        assert_eq!(game.replay().unwrap().ply(), 8);
+        let header = "[Size \"3\"]\n[Komi \"0\"]\n[Result \"0-R\"]\n";
+        assert_eq!(game.to_string(), format!("{}{}\n3. -- b3\n4. c1 c3\n0-R\n", header, tps));
+        let invalid = PtnError::InvalidTag { tag: "TPS".to_string(), value: "x3/x3 1 1".to_string() };
+        assert_eq!("[Size \"3\"]\n[TPS \"x3/x3 1 1\"]".parse::<PtnGame>(), Err(invalid));
+        let unexpected = PtnError::UnexpectedMoveNumber { expected: 1, found: "2.".to_string() };
+        assert_eq!("[Size \"3\"]\n1. a1 2. c3".parse::<PtnGame>(), Err(unexpected));
+        assert!("[Size \"3\"]\n1. -- a1".parse::<PtnGame>().is_err());
+    }
+
+    #[test]
+    fn test_whole_stack_slide() {
+        let mut game = PtnGame::new(&GameConfig::standard(3).unwrap());
+        game.tps = Some("x3/x,121,x/x3 1 3".to_string());
+        let b2 = Position::new(1, 1);
+        let slide = Move { action: Action::Slide(b2, Direction::East, None), player: Color::Red };
+        game.extend_moves(vec![slide]).unwrap();
+        assert_eq!(game.moves()[0].action, Action::Slide(b2, Direction::East, Some(vec![3])));
+        let written = game.to_string();
+        assert!(written.contains("\n3. 3b2>\n"));
+        assert_eq!(written.parse::<PtnGame>().unwrap(), game);
+        let reason = IllegalMove::EmptySquare(b2);
+        let illegal = PtnError::IllegalMove { number: 3, ptn: "b2>".to_string(), reason };
+        let slide = Move { action: Action::Slide(b2, Direction::East, None), player: Color::Blk };
+        assert_eq!(game.extend_moves(vec![slide]), Err(illegal));
+        assert_eq!(game.moves().len(), 1);
+    }
+
+    #[test]
+    fn test_malformed() {
+        assert_eq!("1. a1 c3".parse::<PtnGame>(), Err(PtnError::MissingSize));
+        assert_eq!("[Size 3]".parse::<PtnGame>(), Err(PtnError::MalformedTag("[Size 3]".to_string())));
+        let invalid = PtnError::InvalidTag { tag: "Size".to_string(), value: "9".to_string() };
+        assert_eq!("[Size \"9\"]".parse::<PtnGame>(), Err(invalid));
+        let error = Box::new(PtnError::MissingDirection("3c3".to_string()));
+        assert_eq!("[Size \"3\"]\n1. a1 3c3".parse::<PtnGame>(), Err(PtnError::InvalidMove { number: 1, error }));
+        let unexpected = PtnError::UnexpectedMoveNumber { expected: 2, found: "3.".to_string() };
+        assert_eq!("[Size \"3\"]\n1. a1 c3\n3. b2".parse::<PtnGame>(), Err(unexpected));
+        assert_eq!("[Size \"3\"]\n1. a1 {c3".parse::<PtnGame>(), Err(PtnError::UnclosedComment));
+    }
+}
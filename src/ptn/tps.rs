@@ -0,0 +1,300 @@
+use crate::board::piece::{Piece, PieceKind, Stack};
+use crate::board::{Board, BoardError, Position};
+use crate::player::Color;
+use crate::simulator::config::{GameConfig, MAX_SIZE, MIN_SIZE};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+
+/// A position in Tak Positional System, e.g. `x2,12,2S/x,22S,x2/1,x3/x4 1 6`.
+/// Rows are listed from the top of the board, i.e. the row with the highest index, each square
+/// lists its stack from the bottom with `1` for Red and `2` for Blk. The reserves are implied by
+/// the pieces on the board.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tps {
+    pub board: Board,
+    pub to_move: Color,
+    /// The PTN move number, starting at 1 and increasing after each of Blk's moves.
+    pub move_number: usize,
+}
+
+impl Tps {
+    /// Parses `s` for a game played with `config`, which determines the reserves.
+    /// Fails if the size of `config` is not supported.
+    pub fn parse(s: &str, config: &GameConfig) -> Result<Tps, TpsError> {
+        if !(MIN_SIZE..=MAX_SIZE).contains(&config.size) {
+            return Err(TpsError::UnsupportedSize(config.size));
+        }
+        let mut fields = s.split_whitespace();
+        let (rows, to_move, number) = match (fields.next(), fields.next(), fields.next()) {
+            (Some(rows), Some(to_move), Some(number)) => (rows, to_move, number),
+            _ => return Err(TpsError::MissingField),
+        };
+        if let Some(field) = fields.next() {
+            return Err(TpsError::TrailingField(field.to_string()));
+        }
+        let rows: Vec<&str> = rows.split('/').collect();
+        if rows.len() != config.size {
+            return Err(TpsError::SizeMismatch { expected: config.size, found: rows.len() });
+        }
+        let mut board = Board::new(config).expect("The size was checked above.");
+        for (i, row) in rows.iter().enumerate() {
+            let row_index = config.size - 1 - i;
+            let stacks = parse_row(row, row_index + 1, config.size)?;
+            if stacks.len() != config.size {
+                return Err(TpsError::RowLength { row: row_index + 1, len: stacks.len() });
+            }
+            for (col, stack) in stacks.into_iter().enumerate() {
+                board.try_set(Position::new(row_index, col), stack).map_err(|e| match e {
+                    BoardError::NoPiecesLeft(c, kind) => TpsError::TooManyPieces(c, kind),
+                    _ => unreachable!("Only the reserves can be exceeded."),
+                })?;
+            }
+        }
+        let to_move = match to_move {
+            "1" => Color::Red,
+            "2" => Color::Blk,
+            _ => return Err(TpsError::InvalidPlayer(to_move.to_string())),
+        };
+        let move_number = number.parse().ok().filter(|n| *n > 0);
+        let move_number = move_number.ok_or_else(|| TpsError::InvalidMoveNumber(number.to_string()))?;
+        Ok(Tps { board, to_move, move_number })
+    }
+
+    /// The position on `board` after `ply` plies, the inverse of `Tps::ply`.
+    pub fn at_ply(board: Board, ply: usize) -> Tps {
+        let to_move = match ply % 2 {
+            0 => Color::Red,
+            _ => Color::Blk,
+        };
+        Tps { board, to_move, move_number: ply / 2 + 1 }
+    }
+
+    /// The number of plies played before reaching this position.
+    pub fn ply(&self) -> usize {
+        let blk = match self.to_move {
+            Color::Red => 0,
+            Color::Blk => 1,
+        };
+        2 * (self.move_number - 1) + blk
+    }
+}
+
+/// Parses a row like `x3,12,2S` into its stacks, from left to right.
+/// Fails before creating more than `size` empty squares; `number` is the row's rank for errors.
+fn parse_row(row: &str, number: usize, size: usize) -> Result<Vec<Stack>, TpsError> {
+    let mut res = Vec::new();
+    for square in row.split(',') {
+        let invalid = || TpsError::InvalidSquare(square.to_string());
+        if let Some(count) = square.strip_prefix('x') {
+            let count = match count {
+                "" => 1,
+                n => n.parse().ok().filter(|n| *n > 0).ok_or_else(invalid)?,
+            };
+            if count > size.saturating_sub(res.len()) {
+                return Err(TpsError::RowLength { row: number, len: res.len().saturating_add(count) });
+            }
+            res.extend((0..count).map(|_| Stack::empty()));
+            continue;
+        }
+        let (colors, kind) = match square.chars().last() {
+            Some('S') => (&square[..square.len() - 1], PieceKind::StandingStone),
+            Some('C') => (&square[..square.len() - 1], PieceKind::CapStone),
+            _ => (square, PieceKind::Stone),
+        };
+        if colors.is_empty() {
+            return Err(invalid());
+        }
+        let mut pieces = colors
+            .chars()
+            .map(|c| match c {
+                '1' => Some(Piece::new(PieceKind::Stone, Color::Red)),
+                '2' => Some(Piece::new(PieceKind::Stone, Color::Blk)),
+                _ => None,
+            })
+            .collect::<Option<Vec<Piece>>>()
+            .ok_or_else(invalid)?;
+        pieces.last_mut().unwrap().kind = kind;
+        res.push(Stack::from(pieces));
+    }
+    Ok(res)
+}
+
+impl FromStr for Tps {
+    type Err = TpsError;
+
+    /// Parses `s` assuming the standard reserves for the size of the board.
+    fn from_str(s: &str) -> Result<Tps, TpsError> {
+        let size = s.split_whitespace().next().map(|rows| rows.split('/').count()).unwrap_or(0);
+        let config = GameConfig::standard(size).map_err(|_| TpsError::UnsupportedSize(size))?;
+        Tps::parse(s, &config)
+    }
+}
+
+impl Display for Tps {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let size = self.board.size();
+        for row in (0..size).rev() {
+            let mut empty = 0;
+            let mut squares = Vec::new();
+            for col in 0..size {
+                let stack = &self.board[Position::new(row, col)];
+                if stack.is_empty() {
+                    empty += 1;
+                    continue;
+                }
+                if empty > 0 {
+                    squares.push(empties(empty));
+                    empty = 0;
+                }
+                squares.push(square(stack));
+            }
+            if empty > 0 {
+                squares.push(empties(empty));
+            }
+            write!(f, "{}", squares.join(","))?;
+            if row > 0 {
+                write!(f, "/")?;
+            }
+        }
+        let to_move = match self.to_move {
+            Color::Red => 1,
+            Color::Blk => 2,
+        };
+        write!(f, " {} {}", to_move, self.move_number)
+    }
+}
+
+fn empties(n: usize) -> String {
+    match n {
+        1 => "x".to_string(),
+        n => format!("x{}", n),
+    }
+}
+
+fn square(stack: &Stack) -> String {
+    let mut res: String = stack
+        .iter()
+        .map(|p| match p.color {
+            Color::Red => '1',
+            Color::Blk => '2',
+        })
+        .collect();
+    match stack.top().map(|p| p.kind) {
+        Some(PieceKind::StandingStone) => res.push('S'),
+        Some(PieceKind::CapStone) => res.push('C'),
+        _ => {}
+    }
+    res
+}
+
+/// The reason why a string is not valid TPS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TpsError {
+    /// TPS consists of the rows, the player to move, and the move number.
+    MissingField,
+    /// The contained field follows the move number.
+    TrailingField(String),
+    /// The number of rows or the configured size is outside of `MIN_SIZE..=MAX_SIZE`.
+    UnsupportedSize(usize),
+    /// The board has a different number of rows than the configuration demands.
+    SizeMismatch { expected: usize, found: usize },
+    /// The row, counting from 1 at the bottom, has the wrong number of squares.
+    RowLength { row: usize, len: usize },
+    /// The contained square is neither a stack like `12S` nor empty squares like `x3`.
+    InvalidSquare(String),
+    /// The player to move needs to be `1` or `2`.
+    InvalidPlayer(String),
+    /// The move number needs to be positive.
+    InvalidMoveNumber(String),
+    /// The board holds more pieces of this kind than the player's reserve.
+    TooManyPieces(Color, PieceKind),
+}
+
+impl Display for TpsError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            TpsError::MissingField => write!(f, "TPS needs the rows, the player to move, and the move number."),
+            TpsError::TrailingField(s) => write!(f, "Unexpected '{}' after the move number.", s),
+            TpsError::UnsupportedSize(n) => {
+                write!(f, "Board size {} is not supported, use {} to {}.", n, MIN_SIZE, MAX_SIZE)
+            }
+            TpsError::SizeMismatch { expected, found } => write!(f, "Expected {} rows but found {}.", expected, found),
+            TpsError::RowLength { row, len } => write!(f, "Row {} has {} squares.", row, len),
+            TpsError::InvalidSquare(s) => write!(f, "'{}' is neither a stack like '12S' nor empty like 'x3'.", s),
+            TpsError::InvalidPlayer(s) => write!(f, "'{}' is not a player, use 1 or 2.", s),
+            TpsError::InvalidMoveNumber(s) => write!(f, "'{}' is not a positive move number.", s),
+            TpsError::TooManyPieces(c, kind) => write!(f, "{} does not have enough {:?} for this board.", c, kind),
+        }
+    }
+}
+
+impl std::error::Error for TpsError {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::test_util::*;
+
+    const TPS: &str = "x2,12,2S/x,22S,x2/1,x3/x,2C,x,1 1 6";
+
+    #[test]
+    fn test_parse() {
+        let config = GameConfig { capstones: 1, ..GameConfig::standard(4).unwrap() };
+        let tps = Tps::parse(TPS, &config).unwrap();
+        assert_eq!(tps.to_move, Color::Red);
+        assert_eq!(tps.move_number, 6);
+        assert_eq!(tps.ply(), 10);
+        let board = &tps.board;
+        let stack = &board[Position::new(2, 1)];
+        assert_eq!(stack.iter().map(|p| p.color).collect::<Vec<_>>(), vec![Color::Blk, Color::Blk]);
+        assert_eq!(stack.top().map(|p| p.kind), Some(PieceKind::StandingStone));
+        assert_eq!(board[Position::new(3, 2)], stack_of(&[Color::Red, Color::Blk]));
+        assert_eq!(board[Position::new(3, 3)], single_standing(Color::Blk));
+        assert_eq!(board[Position::new(0, 1)], single_cap(Color::Blk));
+        assert_eq!(board[Position::new(1, 0)], single_stone(Color::Red));
+        assert!(board[Position::new(0, 0)].is_empty());
+        assert_eq!(board.piece_count(Color::Red, PieceKind::Stone), 15 - 3);
+        assert_eq!(board.piece_count(Color::Blk, PieceKind::Stone), 15 - 4);
+        assert_eq!(board.piece_count(Color::Blk, PieceKind::CapStone), 0);
+        assert_eq!(board.piece_count(Color::Red, PieceKind::CapStone), 1);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let config = GameConfig { capstones: 1, ..GameConfig::standard(4).unwrap() };
+        assert_eq!(Tps::parse(TPS, &config).unwrap().to_string(), TPS);
+        let empty = "x5/x5/x5/x5/x5 2 1";
+        let tps: Tps = empty.parse().unwrap();
+        assert_eq!(tps.board, Board::new(&GameConfig::standard(5).unwrap()).unwrap());
+        assert_eq!(tps.ply(), 1);
+        assert_eq!(Tps::at_ply(tps.board.clone(), 1), tps);
+        assert_eq!(tps.to_string(), empty);
+    }
+
+    #[test]
+    fn test_errors() {
+        let parse = |s: &str| s.parse::<Tps>();
+        assert_eq!(parse("x3/x3/x3 1"), Err(TpsError::MissingField));
+        assert_eq!(parse("x3/x3/x3 1 1 x"), Err(TpsError::TrailingField("x".to_string())));
+        assert_eq!(parse("x2/x2 1 1"), Err(TpsError::UnsupportedSize(2)));
+        assert_eq!(parse("x3/x3/x2 1 1"), Err(TpsError::RowLength { row: 1, len: 2 }));
+        assert_eq!(parse("x3/x,x4/x3 1 1"), Err(TpsError::RowLength { row: 2, len: 5 }));
+        let huge = TpsError::RowLength { row: 3, len: usize::MAX };
+        assert_eq!(parse(&format!("x{}/x3/x3 1 1", usize::MAX)), Err(huge));
+        assert_eq!(parse("x3/x3/x,3,x 1 1"), Err(TpsError::InvalidSquare("3".to_string())));
+        assert_eq!(parse("x3/x3/x,S,x 1 1"), Err(TpsError::InvalidSquare("S".to_string())));
+        assert_eq!(parse("x3/x3/x3 3 1"), Err(TpsError::InvalidPlayer("3".to_string())));
+        assert_eq!(parse("x3/x3/x3 1 0"), Err(TpsError::InvalidMoveNumber("0".to_string())));
+        let too_many = TpsError::TooManyPieces(Color::Red, PieceKind::CapStone);
+        assert_eq!(parse("x3/x3/x,1C,x 1 1"), Err(too_many));
+        let mismatch = TpsError::SizeMismatch { expected: 5, found: 3 };
+        assert_eq!(Tps::parse("x3/x3/x3 1 1", &GameConfig::standard(5).unwrap()), Err(mismatch));
+        let nine = GameConfig { size: 9, ..GameConfig::standard(8).unwrap() };
+        assert_eq!(Tps::parse(&format!("{} 1 1", ["x9"; 9].join("/")), &nine), Err(TpsError::UnsupportedSize(9)));
+    }
+
+    fn stack_of(colors: &[Color]) -> Stack {
+        Stack::from(colors.iter().map(|c| stone(*c)).collect::<Vec<_>>())
+    }
+}
@@ -1,63 +1,519 @@
-use crate::actions::Move;
+use crate::actions::{Action, Decision, DrawResponse};
+use crate::analyzer::Metric;
+use crate::board::piece::PieceKind;
+use crate::board::{BitBoard, Board};
 use crate::player::{Color, Player};
+use crate::ptn::{PtnGame, Tps};
+use std::collections::HashMap;
+use std::time::Duration;
 
+pub mod clock;
+pub mod config;
 pub mod game_over;
-mod logic;
+pub mod illegal_move;
+pub mod logic;
+pub mod record;
+pub mod replay;
 
+use self::clock::{Clock, SystemClock};
+use self::config::{ConfigError, GameConfig};
 use self::game_over::Outcome;
+use self::illegal_move::IllegalMove;
 use self::logic::Logic;
+use self::record::{GameRecord, RecordedMove};
 use crate::player::PlayerBuilder;
 
 pub struct Simulator<R, B> {
     logic: Logic,
     red: R,
     blk: B,
+    /// Counts how often each position occurred, identified by the board and the player to move.
+    positions: HashMap<(BitBoard, Color), usize>,
+    /// The time each player spent deciding so far.
+    time_used: Metric<Duration>,
+    /// The starting position if the game did not start on an empty board.
+    start: Option<Tps>,
+    /// Every move played so far.
+    moves: Vec<RecordedMove>,
+    /// Measures the time players spend deciding.
+    clock: Box<dyn Clock>,
+    /// The time on the clock when the game started.
+    started: Option<Duration>,
+    /// The record of the game once it is over.
+    record: Option<GameRecord>,
 }
 
 impl<R: Player, B: Player> Simulator<R, B> {
     /// Red always starts!
-    pub fn new<X, Y>(red: X, blk: Y, size: usize) -> Simulator<R, B>
+    /// Fails if `config` does not describe a playable game.
+    pub fn new<X, Y>(red: X, blk: Y, config: GameConfig) -> Result<Simulator<R, B>, ConfigError>
     where
         X: PlayerBuilder<R>,
         Y: PlayerBuilder<B>,
     {
-        let red = red.setup(size, Color::Red, true);
-        let blk = blk.setup(size, Color::Blk, false);
-        Simulator { logic: Logic::new(size), red, blk }
+        config.validate()?;
+        Ok(Simulator::with_logic(red, blk, Logic::new(config)))
     }
 
-    pub fn start(mut self) -> Outcome {
-        self.red.welcome(&self.blk.name());
-        self.blk.welcome(&self.red.name());
+    /// Continues a game on `board` after `ply` plies, e.g. from a `Tps`. The parity of `ply`
+    /// determines the player to move; the opening is skipped if `ply` is at least two.
+    /// Fails if `config` does not describe a playable game or does not match the position, see
+    /// `Logic::try_from_position`.
+    pub fn from_position<X, Y>(
+        red: X,
+        blk: Y,
+        config: GameConfig,
+        board: Board,
+        ply: usize,
+    ) -> Result<Simulator<R, B>, ConfigError>
+    where
+        X: PlayerBuilder<R>,
+        Y: PlayerBuilder<B>,
+    {
+        let start = Tps::at_ply(board.clone(), ply);
+        let sim = Simulator::with_logic(red, blk, Logic::try_from_position(config, board, ply)?);
+        Ok(Simulator { start: Some(start), ..sim })
+    }
+
+    fn with_logic<X, Y>(red: X, blk: Y, logic: Logic) -> Simulator<R, B>
+    where
+        X: PlayerBuilder<R>,
+        Y: PlayerBuilder<B>,
+    {
+        let first = logic.to_move();
+        let red = red.setup(logic.config(), Color::Red, first == Color::Red);
+        let blk = blk.setup(logic.config(), Color::Blk, first == Color::Blk);
+        let mut positions = HashMap::new();
+        positions.insert((logic.peek().clone(), first), 1);
+        Simulator {
+            logic,
+            red,
+            blk,
+            positions,
+            time_used: Metric::new(),
+            start: None,
+            moves: Vec::new(),
+            clock: Box::new(SystemClock::new()),
+            started: None,
+            record: None,
+        }
+    }
 
-        let pos_red = self.red.first_action(&self.logic.peek());
-        self.logic.first_turn(pos_red, Color::Red);
-        let pos_black = self.blk.first_action(&self.logic.peek());
-        self.logic.first_turn(pos_black, Color::Blk);
+    /// Measures the time players spend deciding with `clock` instead of the wall clock.
+    pub fn with_clock<C: Clock + 'static>(self, clock: C) -> Simulator<R, B> {
+        Simulator { clock: Box::new(clock), ..self }
+    }
+
+    /// Plays the game until it is over. A simulator plays a single game only.
+    pub fn start(&mut self) -> Outcome {
+        assert!(self.record.is_none(), "The game is already over.");
+        self.started = Some(self.clock.elapsed());
+        self.red.welcome(self.blk.name());
+        self.blk.welcome(self.red.name());
 
-        let mut next = Color::Red;
         loop {
-            if let Some(outcome) = self.next_move(next) {
+            let (next, opening) = (self.logic.to_move(), self.logic.in_opening());
+            if let Some(outcome) = self.play_turn(next, opening) {
                 self.game_over(&outcome);
                 return outcome;
-            } else {
-                next = !next;
             }
         }
     }
 
-    fn next_move(&mut self, c: Color) -> Option<Outcome> {
-        let last = self.logic.last_applied_move().map(|m| m.action);
-        let action = match c {
-            Color::Red => self.red.action_for(self.logic.peek(), last),
-            Color::Blk => self.blk.action_for(self.logic.peek(), last),
-        };
-        let mv = Move { action, player: c };
-        self.logic.apply(mv)
+    /// Asks `c` for a decision until it is legal or `c` runs out of retries, in which case they
+    /// forfeit the game. Draw offers are relayed to the opponent, whose clock runs while they
+    /// respond. Repeating a declined offer in the same turn is rejected but does not use up a retry.
+    fn play_turn(&mut self, c: Color, opening: bool) -> Option<Outcome> {
+        let mut retries = 0;
+        let mut offered_draw = false;
+        loop {
+            let started = self.clock.elapsed();
+            let decision = self.request_decision(c, opening);
+            if self.charge_time(c, started) {
+                return Some(self.logic.timeout(c));
+            }
+            let reason = match decision {
+                Decision::Play(ref action) => {
+                    let mv = self.logic.move_for(action.clone());
+                    match self.logic.check(&mv) {
+                        Ok(()) => {
+                            let ply = self.logic.ply();
+                            let outcome = self.logic.apply(mv);
+                            let mv = self.logic.last_applied_move().expect("A move was just applied.");
+                            let elapsed = self.started.map(|started| self.clock.elapsed() - started);
+                            self.moves.push(RecordedMove { mv, ply, elapsed, comment: None });
+                            return outcome.or_else(|| self.check_limits());
+                        }
+                        Err(reason) => reason,
+                    }
+                }
+                Decision::Resign => return Some(self.logic.resign(c)),
+                Decision::OfferDraw if !offered_draw => {
+                    offered_draw = true;
+                    let started = self.clock.elapsed();
+                    let board = Board::from(self.logic.peek());
+                    let response = match c {
+                        Color::Red => self.blk.respond_to_draw(&board),
+                        Color::Blk => self.red.respond_to_draw(&board),
+                    };
+                    if self.charge_time(!c, started) {
+                        return Some(self.logic.timeout(!c));
+                    }
+                    match response {
+                        DrawResponse::Accept => return Some(self.logic.agree_draw()),
+                        DrawResponse::Decline => continue,
+                    }
+                }
+                Decision::OfferDraw => {
+                    match c {
+                        Color::Red => self.red.reject(&decision, &IllegalMove::RepeatedDrawOffer),
+                        Color::Blk => self.blk.reject(&decision, &IllegalMove::RepeatedDrawOffer),
+                    }
+                    continue;
+                }
+            };
+            match c {
+                Color::Red => self.red.reject(&decision, &reason),
+                Color::Blk => self.blk.reject(&decision, &reason),
+            }
+            if retries == self.logic.config().illegal_move_retries {
+                return Some(self.logic.forfeit(c, reason));
+            }
+            retries += 1;
+        }
+    }
+
+    /// Adds the time since `started` to the time used by `c` and determines whether they exceeded
+    /// the time limit.
+    fn charge_time(&mut self, c: Color, started: Duration) -> bool {
+        *self.time_used.of_mut(c) += self.clock.elapsed() - started;
+        self.logic.config().time_limit.map(|limit| self.time_used.of(c) > limit).unwrap_or(false)
+    }
+
+    /// Ends the game if the current position occurred too often or the move limit is reached.
+    fn check_limits(&mut self) -> Option<Outcome> {
+        let position = (self.logic.peek().clone(), self.logic.to_move());
+        let seen = self.positions.entry(position).or_insert(0);
+        *seen += 1;
+        let config = self.logic.config();
+        if config.repetition_limit.map(|n| *seen >= n).unwrap_or(false) {
+            Some(self.logic.repetition())
+        } else if config.move_limit.map(|n| self.logic.ply() >= n).unwrap_or(false) {
+            Some(self.logic.adjudicate())
+        } else {
+            None
+        }
+    }
+
+    fn request_decision(&mut self, c: Color, opening: bool) -> Decision {
+        let board = Board::from(self.logic.peek());
+        if opening {
+            let pos = match c {
+                Color::Red => self.red.first_action(&board),
+                Color::Blk => self.blk.first_action(&board),
+            };
+            Decision::Play(Action::Place(pos, PieceKind::Stone))
+        } else {
+            let last = self.logic.last_applied_move().map(|m| m.action);
+            match c {
+                Color::Red => self.red.action_for(&board, last),
+                Color::Blk => self.blk.action_for(&board, last),
+            }
+        }
+    }
+
+    /// The PTN record of the game once it is over.
+    pub fn ptn(&self) -> Option<PtnGame> {
+        self.record.as_ref().map(|record| record.to_ptn().expect("The simulator only records legal moves."))
+    }
+
+    /// The time each player spent deciding so far.
+    pub fn time_used(&self) -> Metric<Duration> {
+        self.time_used
+    }
+
+    /// The record of the game once it is over.
+    pub fn record(&self) -> Option<&GameRecord> {
+        self.record.as_ref()
     }
 
     fn game_over(&mut self, outcome: &Outcome) {
-        self.red.accept_outcome(&outcome);
-        self.blk.accept_outcome(&outcome)
+        self.red.accept_outcome(outcome);
+        self.blk.accept_outcome(outcome);
+        self.record = Some(GameRecord {
+            config: self.logic.config().clone(),
+            red: self.red.name().clone(),
+            blk: self.blk.name().clone(),
+            start: self.start.clone(),
+            moves: self.moves.clone(),
+            outcome: outcome.clone(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::actions::Move;
+    use crate::board::{Direction, Position};
+    use crate::simulator::game_over::{MatchResult, Termination};
+    use crate::test_util::{ManualClock, ScriptedPlayer};
+
+    fn place(row: usize, col: usize) -> Action {
+        Action::Place(Position::new(row, col), PieceKind::Stone)
+    }
+
+    #[test]
+    fn test_forfeit_after_retries() {
+        let config = GameConfig { illegal_move_retries: 1, ..GameConfig::standard(3).unwrap() };
+        let red = ScriptedPlayer::new("red", vec![Position::new(0, 0)], vec![place(0, 0), place(0, 0)]);
+        let blk = ScriptedPlayer::new("blk", vec![Position::new(2, 2)], Vec::new());
+        let outcome = Simulator::new(red, blk, config).unwrap().start();
+        assert_eq!(outcome.result, MatchResult::Winner(Color::Blk));
+        assert_eq!(outcome.termination, Termination::Forfeit(IllegalMove::OccupiedSquare(Position::new(0, 0))));
+    }
+
+    #[test]
+    fn test_retry_succeeds() {
+        let config = GameConfig { illegal_move_retries: 1, ..GameConfig::standard(3).unwrap() };
+        let off_board = Action::Slide(Position::new(2, 2), Direction::North, Some(vec![1]));
+        let red = ScriptedPlayer::new("red", vec![Position::new(2, 2)], vec![place(0, 0), place(1, 0), place(2, 0)]);
+        let blk = ScriptedPlayer::new("blk", vec![Position::new(0, 2)], vec![off_board, place(2, 1), place(1, 1)]);
+        let outcome = Simulator::new(red, blk, config).unwrap().start();
+        assert_eq!(outcome.result, MatchResult::Winner(Color::Red));
+        assert_eq!(outcome.termination, Termination::Road);
+        assert_eq!(outcome.plies, 7);
+        assert_eq!(outcome.moves.len(), 7);
+        assert_eq!(outcome.moves[6], Move { action: place(2, 0), player: Color::Red });
+        assert_eq!(outcome.score, Some(9 + 6));
+    }
+
+    #[test]
+    fn test_record() {
+        let red = ScriptedPlayer::new("red", vec![Position::new(2, 2)], vec![place(0, 0), place(1, 0), place(2, 0)]);
+        let blk = ScriptedPlayer::new("blk", vec![Position::new(0, 2)], vec![place(2, 1), place(1, 1)]);
+        let mut sim = Simulator::new(red, blk, GameConfig::standard(3).unwrap()).unwrap();
+        assert_eq!(sim.record(), None);
+        let outcome = sim.start();
+        let record = sim.record().unwrap();
+        assert_eq!((record.red.as_str(), record.blk.as_str()), ("red", "blk"));
+        assert_eq!(record.config, GameConfig::standard(3).unwrap());
+        assert_eq!(record.outcome, outcome);
+        assert_eq!(record.moves.iter().map(|m| m.ply).collect::<Vec<_>>(), vec![0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(record.moves.iter().map(|m| m.mv.clone()).collect::<Vec<_>>(), outcome.moves);
+        assert!(record.moves.windows(2).all(|w| w[0].elapsed <= w[1].elapsed));
+
+        let mut record = record.clone();
+        record.moves[2].comment = Some("Heading north.".to_string());
+        assert!(record.to_ptn().unwrap().to_string().contains("2. a1 {Heading north.} b3\n"));
+    }
+
+    #[test]
+    fn test_ptn_export() {
+        let red = ScriptedPlayer::new("red", vec![Position::new(2, 2)], vec![place(0, 0), place(1, 0), place(2, 0)]);
+        let blk = ScriptedPlayer::new("blk", vec![Position::new(0, 2)], vec![place(2, 1), place(1, 1)]);
+        let mut sim = Simulator::new(red, blk, GameConfig::standard(3).unwrap()).unwrap();
+        assert_eq!(sim.ptn(), None);
+        sim.start();
+        let ptn = sim.ptn().unwrap().to_string();
+        let expected = "[Player1 \"red\"]\n[Player2 \"blk\"]\n[Size \"3\"]\n[Komi \"0\"]\n[Result \"R-0\"]\n\n\
+                        1. c3 c1\n2. a1 b3\n3. a2 b2\n4. a3\nR-0\n";
+        assert_eq!(ptn, expected);
+        assert_eq!(ptn.parse::<PtnGame>().unwrap(), sim.ptn().unwrap());
+    }
+
+    #[test]
+    fn test_illegal_opening() {
+        let red = ScriptedPlayer::new("red", vec![Position::new(3, 0)], Vec::new());
+        let blk = ScriptedPlayer::new("blk", Vec::new(), Vec::new());
+        let outcome = Simulator::new(red, blk, GameConfig::standard(3).unwrap()).unwrap().start();
+        assert_eq!(outcome.result, MatchResult::Winner(Color::Blk));
+        assert_eq!(outcome.termination, Termination::Forfeit(IllegalMove::InvalidPosition(Position::new(3, 0))));
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let red = ScriptedPlayer::new("red", Vec::new(), Vec::new());
+        let blk = ScriptedPlayer::new("blk", Vec::new(), Vec::new());
+        let sim = Simulator::new(red, blk, GameConfig { size: 2, ..GameConfig::standard(3).unwrap() });
+        assert_eq!(sim.err(), Some(ConfigError::UnsupportedSize(2)));
+    }
+
+    #[test]
+    fn test_resign() {
+        let red = ScriptedPlayer::deciding("red", vec![Position::new(0, 0)], vec![Decision::Resign]);
+        let blk = ScriptedPlayer::new("blk", vec![Position::new(2, 2)], Vec::new());
+        let outcome = Simulator::new(red, blk, GameConfig::standard(3).unwrap()).unwrap().start();
+        assert_eq!(outcome.result, MatchResult::Winner(Color::Blk));
+        assert_eq!(outcome.termination, Termination::Resignation);
+    }
+
+    #[test]
+    fn test_draw_accepted() {
+        let red = ScriptedPlayer::deciding("red", vec![Position::new(0, 0)], vec![Decision::OfferDraw]);
+        let blk = ScriptedPlayer::new("blk", vec![Position::new(2, 2)], Vec::new()).accepting_draws();
+        let outcome = Simulator::new(red, blk, GameConfig::standard(3).unwrap()).unwrap().start();
+        assert_eq!(outcome.result, MatchResult::Tie);
+        assert_eq!(outcome.termination, Termination::Agreement);
+    }
+
+    #[test]
+    fn test_draw_declined() {
+        let place = |row, col| Decision::Play(Action::Place(Position::new(row, col), PieceKind::Stone));
+        let decisions = vec![Decision::OfferDraw, Decision::OfferDraw, place(0, 0), place(1, 0), place(2, 0)];
+        let red = ScriptedPlayer::deciding("red", vec![Position::new(2, 2)], decisions);
+        let place = |row, col| Action::Place(Position::new(row, col), PieceKind::Stone);
+        let blk = ScriptedPlayer::new("blk", vec![Position::new(0, 2)], vec![place(2, 1), place(1, 1)]);
+        let outcome = Simulator::new(red, blk, GameConfig::standard(3).unwrap()).unwrap().start();
+        assert_eq!(outcome.result, MatchResult::Winner(Color::Red));
+        assert_eq!(outcome.termination, Termination::Road);
+    }
+
+    #[test]
+    fn test_draw_response_uses_responder_clock() {
+        // Blk stays within the limit for their opening but not once they also answer the offer.
+        let clock = ManualClock::default();
+        let config = GameConfig { time_limit: Some(Duration::from_millis(75)), ..GameConfig::standard(3).unwrap() };
+        let red = ScriptedPlayer::deciding("red", vec![Position::new(0, 0)], vec![Decision::OfferDraw]);
+        let blk = ScriptedPlayer::new("blk", vec![Position::new(2, 2)], Vec::new())
+            .thinking(Duration::from_millis(50))
+            .timed_by(&clock);
+        let mut sim = Simulator::new(red, blk, config).unwrap().with_clock(clock);
+        let outcome = sim.start();
+        assert_eq!(outcome.result, MatchResult::Winner(Color::Red));
+        assert_eq!(outcome.termination, Termination::Timeout);
+        assert_eq!(sim.time_used(), Metric::for_values(Duration::from_millis(0), Duration::from_millis(100)));
+    }
+
+    fn shuffle(from: Position, there: Direction, times: usize) -> Vec<Action> {
+        let back = Action::Slide(from.go(there), !there, Some(vec![1]));
+        let forth = Action::Slide(from, there, Some(vec![1]));
+        (0..times).flat_map(|_| vec![forth.clone(), back.clone()]).collect()
+    }
+
+    #[test]
+    fn test_repetition() {
+        let config = GameConfig { repetition_limit: Some(3), ..GameConfig::standard(3).unwrap() };
+        let red =
+            ScriptedPlayer::new("red", vec![Position::new(0, 0)], shuffle(Position::new(2, 2), Direction::West, 2));
+        let blk =
+            ScriptedPlayer::new("blk", vec![Position::new(2, 2)], shuffle(Position::new(0, 0), Direction::East, 2));
+        let outcome = Simulator::new(red, blk, config).unwrap().start();
+        assert_eq!(outcome.result, MatchResult::Tie);
+        assert_eq!(outcome.termination, Termination::Repetition);
+    }
+
+    #[test]
+    fn test_repetition_counts_start() {
+        let config = GameConfig { repetition_limit: Some(3), ..GameConfig::standard(3).unwrap() };
+        let board = "! ! RS  ! ! !  BS ! !".parse().unwrap();
+        let red = ScriptedPlayer::new("red", Vec::new(), shuffle(Position::new(2, 2), Direction::West, 2));
+        let blk = ScriptedPlayer::new("blk", Vec::new(), shuffle(Position::new(0, 0), Direction::East, 2));
+        let outcome = Simulator::from_position(red, blk, config, board, 2).unwrap().start();
+        assert_eq!(outcome.termination, Termination::Repetition);
+        assert_eq!(outcome.plies, 2 + 8);
+    }
+
+    #[test]
+    fn test_move_limit() {
+        let config = GameConfig { move_limit: Some(4), ..GameConfig::standard(3).unwrap() };
+        let red = ScriptedPlayer::new("red", vec![Position::new(0, 0)], vec![place(1, 1)]);
+        let blk =
+            ScriptedPlayer::new("blk", vec![Position::new(2, 2)], shuffle(Position::new(0, 0), Direction::East, 1));
+        let outcome = Simulator::new(red, blk, config).unwrap().start();
+        assert_eq!(outcome.result, MatchResult::Winner(Color::Red));
+        assert_eq!(outcome.termination, Termination::Adjudication);
+        assert_eq!(outcome.flats.of(Color::Red), 2);
+    }
+
+    #[test]
+    fn test_timeout() {
+        let clock = ManualClock::default();
+        let config = GameConfig { time_limit: Some(Duration::from_millis(1)), ..GameConfig::standard(3).unwrap() };
+        let red = ScriptedPlayer::new("red", vec![Position::new(0, 0)], Vec::new());
+        let blk = ScriptedPlayer::new("blk", vec![Position::new(2, 2)], Vec::new())
+            .thinking(Duration::from_millis(5))
+            .timed_by(&clock);
+        let mut sim = Simulator::new(red, blk, config).unwrap().with_clock(clock);
+        let outcome = sim.start();
+        assert_eq!(sim.time_used().of(Color::Blk), Duration::from_millis(5));
+        assert_eq!(outcome.result, MatchResult::Winner(Color::Red));
+        assert_eq!(outcome.termination, Termination::Timeout);
+        assert_eq!(outcome.plies, 1);
+        assert_eq!(outcome.score, None);
+    }
+
+    #[test]
+    fn test_from_position() {
+        let tps: Tps = "2,x2/x,1,x/1,x2 2 3".parse().unwrap();
+        let red = ScriptedPlayer::new("red", Vec::new(), vec![place(0, 2)]);
+        let blk = ScriptedPlayer::new("blk", Vec::new(), vec![place(2, 1), place(2, 2)]);
+        let ply = tps.ply();
+        let mut sim = Simulator::from_position(red, blk, GameConfig::standard(3).unwrap(), tps.board, ply).unwrap();
+        let outcome = sim.start();
+        assert_eq!(outcome.result, MatchResult::Winner(Color::Blk));
+        assert_eq!(outcome.termination, Termination::Road);
+        assert_eq!(outcome.plies, 8);
+        assert_eq!(outcome.first_ply, 5);
+        assert_eq!(outcome.moves.len(), 3);
+        let ptn = sim.ptn().unwrap();
+        assert_eq!(ptn.tps.as_deref(), Some("2,x2/x,1,x/1,x2 2 3"));
+        assert!(ptn.to_string().ends_with("3. -- b3\n4. c1 c3\n0-R\n"));
+    }
+
+    #[test]
+    fn test_from_position_in_opening() {
+        let mut board = Board::new(&GameConfig::standard(3).unwrap()).unwrap();
+        board.place(crate::test_util::stone(Color::Blk), Position::new(2, 2));
+        let red = ScriptedPlayer::new("red", Vec::new(), vec![place(1, 0), place(2, 0)]);
+        let blk = ScriptedPlayer::new("blk", vec![Position::new(0, 0)], vec![place(2, 1)]);
+        let outcome = Simulator::from_position(red, blk, GameConfig::standard(3).unwrap(), board, 1).unwrap().start();
+        assert_eq!(outcome.result, MatchResult::Winner(Color::Red));
+        assert_eq!(outcome.moves[0], Move { action: place(0, 0), player: Color::Red });
+    }
+
+    #[test]
+    fn test_from_position_size_mismatch() {
+        let red = ScriptedPlayer::new("red", Vec::new(), Vec::new());
+        let blk = ScriptedPlayer::new("blk", Vec::new(), Vec::new());
+        let board = Board::new(&GameConfig::standard(4).unwrap()).unwrap();
+        let sim = Simulator::from_position(red, blk, GameConfig::standard(3).unwrap(), board, 2);
+        assert_eq!(sim.err(), Some(ConfigError::BoardSizeMismatch { size: 3, board: 4 }));
+    }
+
+    #[test]
+    fn test_from_position_reserve_mismatch() {
+        let player = || ScriptedPlayer::new("player", Vec::new(), Vec::new());
+        let board: Board = "! ! RS  ! ! !  BS ! !".parse().unwrap();
+        let config = GameConfig { stones: 9, ..GameConfig::standard(3).unwrap() };
+        let sim = Simulator::from_position(player(), player(), config, board.clone(), 2);
+        assert_eq!(sim.err(), Some(ConfigError::ReserveMismatch(Color::Red)));
+        let config = GameConfig { capstones: 1, ..GameConfig::standard(3).unwrap() };
+        let sim = Simulator::from_position(player(), player(), config, board, 2);
+        assert_eq!(sim.err(), Some(ConfigError::ReserveMismatch(Color::Red)));
+    }
+
+    #[test]
+    fn test_from_position_inconsistent() {
+        let player = || ScriptedPlayer::new("player", Vec::new(), Vec::new());
+        let config = GameConfig::standard(3).unwrap();
+        let road: Board = "! ! !  RS RS RS  BS BS !".parse().unwrap();
+        let sim = Simulator::from_position(player(), player(), config.clone(), road, 6);
+        assert_eq!(sim.err(), Some(ConfigError::GameOver));
+        let full: Board = "RS BS RS  BS BS RS  RS RS BS".parse().unwrap();
+        let sim = Simulator::from_position(player(), player(), config.clone(), full, 9);
+        assert_eq!(sim.err(), Some(ConfigError::GameOver));
+        let board: Board = "! ! RS  ! ! !  BS ! !".parse().unwrap();
+        let sim = Simulator::from_position(player(), player(), config.clone(), board.clone(), 1);
+        assert_eq!(sim.err(), Some(ConfigError::PlyMismatch { ply: 1, pieces: 2 }));
+        let sim = Simulator::from_position(
+            player(),
+            player(),
+            config,
+            Board::new(&GameConfig::standard(3).unwrap()).unwrap(),
+            1,
+        );
+        assert_eq!(sim.err(), Some(ConfigError::PlyMismatch { ply: 1, pieces: 0 }));
     }
 }
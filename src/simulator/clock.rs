@@ -0,0 +1,32 @@
+use std::time::{Duration, Instant};
+
+/// Measures the time players spend deciding, see `GameConfig::time_limit`, and timestamps the
+/// recorded moves.
+pub trait Clock {
+    /// The time passed since an arbitrary but fixed moment, e.g. the creation of the clock.
+    fn elapsed(&self) -> Duration;
+}
+
+/// The wall clock, which the `Simulator` uses unless told otherwise.
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    created: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> SystemClock {
+        SystemClock { created: Instant::now() }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> SystemClock {
+        SystemClock::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn elapsed(&self) -> Duration {
+        self.created.elapsed()
+    }
+}
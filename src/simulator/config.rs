@@ -0,0 +1,218 @@
+use crate::player::Color;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::time::Duration;
+
+/// The smallest board size the simulator supports.
+pub const MIN_SIZE: usize = 3;
+/// The largest board size the simulator supports.
+pub const MAX_SIZE: usize = 8;
+/// The most stones and capstones a player may have, so that any stack fits into a `BitBoard`.
+pub const MAX_PIECES: u16 = 64;
+
+/// Describes the setup of a single game.
+/// Construct it via `GameConfig::standard` and adapt the fields for non-standard setups; the
+/// `Simulator` validates the configuration before the game starts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameConfig {
+    /// Width and height of the board.
+    pub size: usize,
+    /// Number of regular stones each player starts with.
+    pub stones: u16,
+    /// Number of capstones each player starts with.
+    pub capstones: u16,
+    /// Flats awarded to the second player when the game is decided by flat count.
+    pub komi: Komi,
+    /// Whether each player places an opponent's stone in their first turn.
+    pub swap_opening: bool,
+    /// How a move completing a road for both players is resolved.
+    pub road_tie: RoadTieRule,
+    /// Maximum number of plies before the game is adjudicated by flat count, if any.
+    pub move_limit: Option<usize>,
+    /// The game ends in a draw once a position occurs this often, if set. A position consists
+    /// of the board and the player to move.
+    pub repetition_limit: Option<usize>,
+    /// How often a player may retry after submitting an illegal move before forfeiting.
+    pub illegal_move_retries: usize,
+    /// The total time each player may spend deciding on their moves, if limited.
+    pub time_limit: Option<Duration>,
+}
+
+impl GameConfig {
+    /// The official setup for a board of width `size`.
+    /// Fails if `size` is outside of `MIN_SIZE..=MAX_SIZE`, as there is no official setup then.
+    pub fn standard(size: usize) -> Result<GameConfig, ConfigError> {
+        let (stones, capstones) = match size {
+            3 => (10, 0),
+            4 => (15, 0),
+            5 => (21, 1),
+            6 => (30, 1),
+            7 => (40, 2),
+            8 => (50, 2),
+            n => return Err(ConfigError::UnsupportedSize(n)),
+        };
+        Ok(GameConfig {
+            size,
+            stones,
+            capstones,
+            komi: Komi::default(),
+            swap_opening: true,
+            road_tie: RoadTieRule::MoverWins,
+            move_limit: None,
+            repetition_limit: None,
+            illegal_move_retries: 0,
+            time_limit: None,
+        })
+    }
+
+    /// Checks whether a game can be played with this configuration.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.size < MIN_SIZE || self.size > MAX_SIZE {
+            return Err(ConfigError::UnsupportedSize(self.size));
+        }
+        if self.stones == 0 {
+            return Err(ConfigError::NoStones);
+        }
+        if u32::from(self.stones) + u32::from(self.capstones) > u32::from(MAX_PIECES) {
+            return Err(ConfigError::TooManyPieces);
+        }
+        if self.move_limit == Some(0) {
+            return Err(ConfigError::ZeroMoveLimit);
+        }
+        if self.repetition_limit.map(|n| n < 2).unwrap_or(false) {
+            return Err(ConfigError::RepetitionLimitTooSmall);
+        }
+        Ok(())
+    }
+}
+
+/// Flats awarded to the second player, counted in half flats so that half-point komi can break
+/// ties.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Komi {
+    half_flats: u16,
+}
+
+impl Komi {
+    /// Komi of `flats` full flats.
+    pub fn flats(flats: u16) -> Komi {
+        Komi { half_flats: 2 * flats }
+    }
+
+    /// Komi of `half_flats` half flats, e.g. `Komi::from_half_flats(5)` is 2.5 flats.
+    pub fn from_half_flats(half_flats: u16) -> Komi {
+        Komi { half_flats }
+    }
+
+    pub fn half_flats(self) -> u16 {
+        self.half_flats
+    }
+}
+
+impl Display for Komi {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self.half_flats % 2 {
+            0 => write!(f, "{}", self.half_flats / 2),
+            _ => write!(f, "{}.5", self.half_flats / 2),
+        }
+    }
+}
+
+/// Resolves a move that completes a road for both players at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoadTieRule {
+    /// The player who made the move wins, as the official rules demand.
+    MoverWins,
+    /// The game ends in a tie, as in early rule sets.
+    Tie,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigError {
+    /// The board size is outside of `MIN_SIZE..=MAX_SIZE`.
+    UnsupportedSize(usize),
+    /// Players need at least one regular stone.
+    NoStones,
+    /// Players may have at most `MAX_PIECES` pieces.
+    TooManyPieces,
+    /// A move limit of zero would end the game before it started.
+    ZeroMoveLimit,
+    /// Every position occurs at least once, so the repetition limit needs to be at least two.
+    RepetitionLimitTooSmall,
+    /// The starting position has a different size than the configuration.
+    BoardSizeMismatch { size: usize, board: usize },
+    /// The pieces of the player on the board and in their stash differ from those of the
+    /// configuration.
+    ReserveMismatch(Color),
+    /// The starting position holds more pieces than `ply` plies can place, or the opening is
+    /// incomplete.
+    PlyMismatch { ply: usize, pieces: usize },
+    /// The game is already over in the starting position.
+    GameOver,
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            ConfigError::UnsupportedSize(n) => {
+                write!(f, "Board size {} is not supported, use {} to {}.", n, MIN_SIZE, MAX_SIZE)
+            }
+            ConfigError::NoStones => write!(f, "Players need at least one stone."),
+            ConfigError::TooManyPieces => write!(f, "Players may have at most {} pieces.", MAX_PIECES),
+            ConfigError::ZeroMoveLimit => write!(f, "The move limit needs to be positive."),
+            ConfigError::RepetitionLimitTooSmall => write!(f, "The repetition limit needs to be at least two."),
+            ConfigError::BoardSizeMismatch { size, board } => {
+                write!(f, "The board has size {} but the game is configured for size {}.", board, size)
+            }
+            ConfigError::ReserveMismatch(c) => {
+                write!(f, "The pieces of {} on the board and in their stash do not match the configuration.", c)
+            }
+            ConfigError::PlyMismatch { ply, pieces } => {
+                write!(f, "A position with {} pieces cannot be reached in {} plies.", pieces, ply)
+            }
+            ConfigError::GameOver => write!(f, "The game is already over in the starting position."),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_standard_valid() {
+        for size in MIN_SIZE..=MAX_SIZE {
+            assert_eq!(GameConfig::standard(size).unwrap().validate(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(GameConfig::standard(2), Err(ConfigError::UnsupportedSize(2)));
+        assert_eq!(GameConfig::standard(9), Err(ConfigError::UnsupportedSize(9)));
+        let config = GameConfig { size: 2, ..GameConfig::standard(3).unwrap() };
+        assert_eq!(config.validate(), Err(ConfigError::UnsupportedSize(2)));
+        let config = GameConfig { size: 9, ..GameConfig::standard(8).unwrap() };
+        assert_eq!(config.validate(), Err(ConfigError::UnsupportedSize(9)));
+        let config = GameConfig { stones: 0, ..GameConfig::standard(5).unwrap() };
+        assert_eq!(config.validate(), Err(ConfigError::NoStones));
+        let config = GameConfig { stones: 63, capstones: 2, ..GameConfig::standard(8).unwrap() };
+        assert_eq!(config.validate(), Err(ConfigError::TooManyPieces));
+        let config = GameConfig { stones: u16::MAX, capstones: 1, ..GameConfig::standard(8).unwrap() };
+        assert_eq!(config.validate(), Err(ConfigError::TooManyPieces));
+        let config = GameConfig { move_limit: Some(0), ..GameConfig::standard(5).unwrap() };
+        assert_eq!(config.validate(), Err(ConfigError::ZeroMoveLimit));
+        let config = GameConfig { repetition_limit: Some(1), ..GameConfig::standard(5).unwrap() };
+        assert_eq!(config.validate(), Err(ConfigError::RepetitionLimitTooSmall));
+    }
+
+    #[test]
+    fn test_komi_display() {
+        assert_eq!(Komi::flats(2).to_string(), "2");
+        assert_eq!(Komi::from_half_flats(5).to_string(), "2.5");
+        assert_eq!(Komi::default().to_string(), "0");
+    }
+}
@@ -1,14 +1,106 @@
+use super::config::Komi;
+use super::illegal_move::IllegalMove;
+use crate::actions::Move;
+use crate::analyzer::Metric;
 use crate::board::Board;
 use crate::player::Color;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Outcome {
     pub result: MatchResult,
+    pub termination: Termination,
     pub board: Board,
+    /// The number of plies played, including the opening and the plies before the starting
+    /// position if the game did not start on an empty board.
+    pub plies: usize,
+    /// The number of plies before the first of `moves`, i.e. zero unless the game started from a
+    /// position. `moves` covers the plies `first_ply..plies`.
+    pub first_ply: usize,
+    /// Every move in the order it was played.
+    pub moves: Vec<Move>,
+    /// Flats controlled by each player at the end of the game, not including komi.
+    pub flats: Metric<u16>,
+    /// Komi added to the second player's flat count.
+    pub komi: Komi,
+    /// The winner's score as on playtak: the number of squares plus the pieces left in the
+    /// winner's reserve, zero in a tie. Only set if a road or the flat count decided the game,
+    /// since playtak does not score resignations, forfeits, timeouts or agreed draws.
+    pub score: Option<u32>,
+}
+
+impl Outcome {
+    /// The flat lead of `player` over their opponent after applying komi.
+    pub fn flat_margin(&self, player: Color) -> f32 {
+        let red = 2 * i32::from(self.flats.of(Color::Red));
+        let blk = 2 * i32::from(self.flats.of(Color::Blk)) + i32::from(self.komi.half_flats());
+        let margin = (red - blk) as f32 / 2.0;
+        match player {
+            Color::Red => margin,
+            Color::Blk => -margin,
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MatchResult {
     Winner(Color),
     Tie,
 }
+
+/// Describes how the game ended.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Termination {
+    /// A player completed a road.
+    Road,
+    /// The board filled up or a player ran out of pieces; the flat count decided.
+    Flats,
+    /// The loser kept submitting illegal moves; contains the reason for the last rejection.
+    Forfeit(IllegalMove),
+    /// The loser resigned.
+    Resignation,
+    /// Both players agreed to a draw.
+    Agreement,
+    /// A position occurred as often as `GameConfig::repetition_limit` permits.
+    Repetition,
+    /// The game reached `GameConfig::move_limit`; the flat count decided.
+    Adjudication,
+    /// The loser exceeded `GameConfig::time_limit`.
+    Timeout,
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+
+    use super::*;
+    use crate::simulator::config::GameConfig;
+    use crate::simulator::logic::Logic;
+    use crate::test_util::check_serde;
+    use serde_json::json;
+
+    #[test]
+    fn test_serde() {
+        check_serde(&MatchResult::Winner(Color::Blk), json!({ "Winner": "Blk" }));
+        check_serde(&MatchResult::Tie, json!("Tie"));
+
+        let mut logic = Logic::new(GameConfig::standard(3).unwrap());
+        let mut outcome = None;
+        for ptn in &["c3", "a1", "a2", "b2", "a3"] {
+            outcome = logic.apply(logic.move_from_ptn(ptn).unwrap());
+        }
+        let outcome: Outcome = outcome.unwrap();
+        let value = serde_json::to_value(&outcome).unwrap();
+        assert_eq!(value["result"], json!({ "Winner": "Red" }));
+        assert_eq!(value["termination"], json!("Road"));
+        assert_eq!(value["plies"], json!(5));
+        assert_eq!(value["first_ply"], json!(0));
+        let last = json!({ "action": { "Place": [{ "row": 2, "col": 0 }, "Stone"] }, "player": "Red" });
+        assert_eq!(value["moves"][4], last);
+        assert_eq!(value["flats"], json!({ "red": 3, "blk": 2 }));
+        assert_eq!(value["komi"], json!({ "half_flats": 0 }));
+        assert_eq!(value["score"], json!(9 + 7));
+        assert_eq!(serde_json::from_value::<Outcome>(value).unwrap(), outcome);
+    }
+}
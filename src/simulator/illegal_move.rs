@@ -0,0 +1,80 @@
+use crate::board::piece::PieceKind;
+use crate::board::Position;
+use crate::player::Color;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// The reason why a `Move` cannot be applied to the current board or a player's decision is
+/// not admissible otherwise.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IllegalMove {
+    /// The position does not exist on the board.
+    InvalidPosition(Position),
+    /// A piece cannot be placed on an occupied position.
+    OccupiedSquare(Position),
+    /// The player has no piece of this kind left in their stash.
+    NoPiecesLeft(PieceKind),
+    /// There is no stack to slide at this position.
+    EmptySquare(Position),
+    /// The stack at this position is controlled by the opponent.
+    WrongOwner(Position),
+    /// A slide needs to carry at least one piece in each step.
+    EmptyCarry,
+    /// The number of carried pieces needs to strictly decrease in each step.
+    NonDecreasingCarry,
+    /// The slide would leave the board.
+    OffBoard,
+    /// The slide carries more pieces than the stack holds.
+    CarryExceedsStack { available: usize, requested: usize },
+    /// A slide may carry at most as many pieces as the board is wide.
+    CarryLimit { limit: usize, requested: usize },
+    /// Only a lone capstone may be dropped onto the standing stone at this position.
+    BlockedByWall(Position),
+    /// Nothing may be dropped onto the capstone at this position.
+    BlockedByCapstone(Position),
+    /// Players may only place a flat stone in their first turn.
+    OpeningRequiresFlat,
+    /// The first stone needs to be of the contained color.
+    OpeningColor(Color),
+    /// It is the contained player's turn.
+    OutOfTurn(Color),
+    /// The opponent already declined a draw offer in this turn.
+    RepeatedDrawOffer,
+}
+
+impl Display for IllegalMove {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            IllegalMove::InvalidPosition(pos) => write!(f, "{} is not on the board.", pos),
+            IllegalMove::OccupiedSquare(pos) => write!(f, "{} is already occupied.", pos),
+            IllegalMove::NoPiecesLeft(kind) => write!(f, "There is no {:?} left in the stash.", kind),
+            IllegalMove::EmptySquare(pos) => write!(f, "There is no stack at {}.", pos),
+            IllegalMove::WrongOwner(pos) => {
+                write!(f, "The stack at {} belongs to the opponent.", pos)
+            }
+            IllegalMove::EmptyCarry => write!(f, "Each step of a slide needs to carry at least one piece."),
+            IllegalMove::NonDecreasingCarry => {
+                write!(f, "The number of carried pieces needs to decrease in each step.")
+            }
+            IllegalMove::OffBoard => write!(f, "The slide would leave the board."),
+            IllegalMove::CarryExceedsStack { available, requested } => {
+                write!(f, "Cannot carry {} pieces from a stack of {}.", requested, available)
+            }
+            IllegalMove::CarryLimit { limit, requested } => {
+                write!(f, "Cannot carry {} pieces, the limit is {}.", requested, limit)
+            }
+            IllegalMove::BlockedByWall(pos) => {
+                write!(f, "Only a lone capstone can flatten the standing stone at {}.", pos)
+            }
+            IllegalMove::BlockedByCapstone(pos) => {
+                write!(f, "The capstone at {} blocks the slide.", pos)
+            }
+            IllegalMove::OpeningRequiresFlat => write!(f, "The first stone needs to be a flat stone."),
+            IllegalMove::OpeningColor(c) => write!(f, "The first stone needs to be {}.", c),
+            IllegalMove::OutOfTurn(c) => write!(f, "It is {}'s turn.", c),
+            IllegalMove::RepeatedDrawOffer => write!(f, "The opponent already declined a draw in this turn."),
+        }
+    }
+}
+
+impl std::error::Error for IllegalMove {}
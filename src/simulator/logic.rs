@@ -1,39 +1,160 @@
-use super::game_over::{MatchResult, Outcome};
+use super::config::{ConfigError, GameConfig, RoadTieRule};
+use super::game_over::{MatchResult, Outcome, Termination};
+use super::illegal_move::IllegalMove;
 use crate::actions::{Action, Move};
-use crate::board::piece::{Piece, PieceKind};
-use crate::board::{Board, Direction, Position};
+use crate::analyzer::Metric;
+use crate::board::piece::{Piece, PieceKind, Stack};
+use crate::board::{BitBoard, Board, BoardError, Direction, Position};
 use crate::player::Color;
+use crate::ptn::PtnError;
 
-use std::collections::HashSet;
+use std::cmp::Ordering;
 
-pub(crate) struct Logic {
-    board: Board,
-    last_applied_move: Option<Move>,
+pub(crate) mod movegen;
+mod perft;
+pub(crate) mod roads;
+
+use self::roads::Roads;
+
+/// Reverts a move applied via `Logic::make`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Undo {
+    change: Change,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Change {
+    Placed(Position),
+    /// `flattened` indicates whether the last step flattened a standing stone.
+    Slid {
+        src: Position,
+        dir: Direction,
+        carries: Vec<usize>,
+        flattened: bool,
+    },
+}
+
+/// Enforces the rules of Tak on a board.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Logic {
+    board: BitBoard,
+    config: GameConfig,
+    /// Every move applied so far, in order. Slides always carry an explicit list of carries.
+    moves: Vec<Move>,
+    ply: usize,
+    /// Kept up to date with the board by `Logic::make` and `Logic::unmake`.
+    roads: Roads,
 }
 
 impl Logic {
-    pub(crate) fn new(size: usize) -> Logic {
-        Logic { board: Board::new(size), last_applied_move: None }
+    /// Assumes `config` to be valid.
+    pub fn new(config: GameConfig) -> Logic {
+        let board = Board::new(&config).expect("A valid config fits on a board.");
+        Logic::from_position(config, board, 0)
+    }
+
+    /// Continues a game on `board` after `ply` plies; the parity of `ply` determines the player
+    /// to move. Assumes `config` to be valid and to match the board.
+    pub fn from_position(config: GameConfig, board: Board, ply: usize) -> Logic {
+        let bits = BitBoard::from(&board);
+        Logic { roads: Roads::build(&bits), board: bits, config, moves: Vec::new(), ply }
+    }
+
+    /// Continues a game like `Logic::from_position`, but fails if `config` is invalid or does not
+    /// match the position, or if the game is already over.
+    pub fn try_from_position(config: GameConfig, board: Board, ply: usize) -> Result<Logic, ConfigError> {
+        config.validate()?;
+        if board.size() != config.size {
+            return Err(ConfigError::BoardSizeMismatch { size: config.size, board: board.size() });
+        }
+        for &c in &[Color::Red, Color::Blk] {
+            for &(kind, total) in &[(PieceKind::Stone, config.stones), (PieceKind::CapStone, config.capstones)] {
+                if u32::from(board.placed_count(c, kind)) + u32::from(board.piece_count(c, kind)) != u32::from(total) {
+                    return Err(ConfigError::ReserveMismatch(c));
+                }
+            }
+        }
+        // Every ply places at most one piece, and the opening places exactly one per ply.
+        let pieces = board.iter().map(Stack::len).sum();
+        if pieces > ply || (ply < 2 && pieces != ply) {
+            return Err(ConfigError::PlyMismatch { ply, pieces });
+        }
+        let logic = Logic::from_position(config, board, ply);
+        if logic.is_over() {
+            return Err(ConfigError::GameOver);
+        }
+        Ok(logic)
     }
 
     /// Create an ActionLogic for a given board.
     /// Note it is assumed that the last applied move is `None` even if there is only one
-    /// logical choice.
+    /// logical choice, and that the opening is over with Red to move.
     #[cfg(test)]
     fn from_board(board: Board) -> Logic {
-        Logic { board, last_applied_move: None }
+        Logic::from_position(GameConfig::standard(board.size()).unwrap(), board, 2)
     }
 
-    pub(crate) fn peek(&self) -> &Board {
+    /// The current board in the compact representation the logic works on. It answers queries
+    /// like `BitBoard::stack` directly; convert it via `Board::from` where a `Board` is needed.
+    pub fn peek(&self) -> &BitBoard {
         &self.board
     }
 
-    pub(crate) fn first_turn(&mut self, pos: Position, c: Color) {
-        self.apply(Move { player: !c, action: Action::Place(pos, PieceKind::Stone) });
+    /// The move placing the first stone of `c` at `pos`.
+    pub fn opening_move(&self, pos: Position, c: Color) -> Move {
+        Move { player: self.opening_color(c), action: Action::Place(pos, PieceKind::Stone) }
+    }
+
+    /// The move performing `action` on behalf of the player to move, i.e. placing an opponent's
+    /// stone in the opening if it swaps.
+    pub fn move_for(&self, action: Action) -> Move {
+        let player = if self.in_opening() { self.opening_color(self.to_move()) } else { self.to_move() };
+        Move { player, action }
+    }
+
+    /// Parses the PTN move `ptn` on behalf of the player to move, see `Logic::move_for`.
+    pub fn move_from_ptn(&self, ptn: &str) -> Result<Move, PtnError> {
+        Ok(self.move_for(ptn.parse()?))
+    }
+
+    /// The color of the stone `c` places in their first turn.
+    fn opening_color(&self, c: Color) -> Color {
+        if self.config.swap_opening {
+            !c
+        } else {
+            c
+        }
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    pub fn last_applied_move(&self) -> Option<Move> {
+        self.moves.last().cloned()
     }
 
-    pub(crate) fn last_applied_move(&mut self) -> Option<Move> {
-        self.last_applied_move.clone()
+    /// Every move applied so far, in order.
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// The number of moves applied so far.
+    pub fn ply(&self) -> usize {
+        self.ply
+    }
+
+    /// The player whose turn it is. Red always starts!
+    pub fn to_move(&self) -> Color {
+        match self.ply % 2 {
+            0 => Color::Red,
+            _ => Color::Blk,
+        }
+    }
+
+    /// Determines whether the players are still placing their first stones.
+    pub fn in_opening(&self) -> bool {
+        self.ply < 2
     }
 
     fn size(&self) -> usize {
@@ -44,147 +165,271 @@ impl Logic {
         self.board.valid_pos(pos)
     }
 
-    pub(crate) fn applicable(&self, mv: &Move) -> bool {
+    pub fn applicable(&self, mv: &Move) -> bool {
+        self.check(mv).is_ok()
+    }
+
+    /// Determines whether `mv` can be applied to the current board and explains why not
+    /// otherwise.
+    /// In the opening, `mv` needs to place a flat stone of the color dictated by
+    /// `GameConfig::swap_opening`; afterwards `mv.player` needs to be the player to move.
+    pub fn check(&self, mv: &Move) -> Result<(), IllegalMove> {
+        if self.in_opening() {
+            let expected = self.opening_color(self.to_move());
+            match mv.action {
+                Action::Place(_, PieceKind::Stone) if mv.player == expected => {}
+                Action::Place(_, PieceKind::Stone) => return Err(IllegalMove::OpeningColor(expected)),
+                _ => return Err(IllegalMove::OpeningRequiresFlat),
+            }
+        } else if mv.player != self.to_move() {
+            return Err(IllegalMove::OutOfTurn(self.to_move()));
+        }
         match mv.action {
             Action::Place(pos, kind) => {
-                self.valid_pos(pos) && self.board[pos].is_empty() && self.board.piece_count(mv.player, kind) > 0
+                if !self.valid_pos(pos) {
+                    return Err(IllegalMove::InvalidPosition(pos));
+                }
+                if !self.board.is_empty(pos) {
+                    return Err(IllegalMove::OccupiedSquare(pos));
+                }
+                if self.board.piece_count(mv.player, kind) == 0 {
+                    return Err(IllegalMove::NoPiecesLeft(kind));
+                }
+                Ok(())
             }
             Action::Slide(pos, dir, ref v) => {
+                if !self.valid_pos(pos) {
+                    return Err(IllegalMove::InvalidPosition(pos));
+                }
+                let height = self.board.height(pos);
+                match self.board.color(pos) {
+                    None => return Err(IllegalMove::EmptySquare(pos)),
+                    Some(c) if c != mv.player => return Err(IllegalMove::WrongOwner(pos)),
+                    Some(_) => {}
+                }
+                let v = v.clone().unwrap_or_else(|| vec![height]);
+                if v.is_empty() || v.contains(&0) {
+                    return Err(IllegalMove::EmptyCarry);
+                }
+                if v.windows(2).any(|w| w[1] >= w[0]) {
+                    return Err(IllegalMove::NonDecreasingCarry);
+                }
                 let size = self.size(); // Abbreviation
-                let v = v.as_ref().map(Vec::clone).unwrap_or_else(|| vec![self.board[pos].len()]);
-
-                let val_pos = || self.valid_pos(pos);
-                let empty = || v.is_empty();
-                let contains_0 = || v.iter().any(|n| *n == 0);
-                let decreasing = || {
-                    let mut last = v.first().unwrap();
-                    for current in &v[1..] {
-                        if current >= last {
-                            return false;
-                        }
-                        last = current;
-                    }
-                    true
-                };
-                let color = || self.board[pos].color().map(|c| c == mv.player).unwrap_or(true);
-                let oob = || match dir {
-                    Direction::North => pos.row + v.len() > size,
-                    Direction::East => pos.col + v.len() > size,
-                    Direction::South => pos.row < v.len(),
-                    Direction::West => pos.col < v.len(),
-                };
-                let all_compatible = || {
-                    let mut src = pos;
-                    let original_stack = &self.board[src];
-                    if original_stack.len() < *v.first().unwrap() {
-                        return false;
-                    }
-                    if original_stack.color().map(|c| c != mv.player).unwrap_or(false) {
-                        return false; // If there is a stack, it's color must be the player's.
-                    }
-                    let mut full = original_stack.clone();
-                    for n in &v {
-                        let dst = src.go(dir);
-                        let carried = full.peek_from_top(*n);
-                        if !self.board[dst].compatible_with(&carried) {
-                            return false;
-                        }
-                        src = dst;
-                        full = carried;
+                if v.len() > self.board.topology().distance_to_border(pos, dir) {
+                    return Err(IllegalMove::OffBoard);
+                }
+                if height < v[0] {
+                    return Err(IllegalMove::CarryExceedsStack { available: height, requested: v[0] });
+                }
+                if v[0] > size {
+                    return Err(IllegalMove::CarryLimit { limit: size, requested: v[0] });
+                }
+                // The top piece leads the carried pieces in every step.
+                let kind = self.board.top(pos).expect("The stack is owned by the player.").kind;
+                let mut src = pos;
+                for n in &v {
+                    let dst = src.go(dir);
+                    match self.board.check_compatible(dst, kind, *n) {
+                        Err(BoardError::BlockedByCapstone) => return Err(IllegalMove::BlockedByCapstone(dst)),
+                        Err(_) => return Err(IllegalMove::BlockedByWall(dst)),
+                        Ok(()) => {}
                     }
-                    true
-                };
-                val_pos() && !empty() && !contains_0() && decreasing() && color() && !oob() && all_compatible()
+                    src = dst;
+                }
+                Ok(())
             }
         }
     }
 
-    pub(crate) fn apply(&mut self, mv: Move) -> Option<Outcome> {
+    /// Applies `mv` and reports the outcome if it ends the game.
+    /// Assumes `mv` to be applicable, see `Logic::check`.
+    pub fn apply(&mut self, mv: Move) -> Option<Outcome> {
+        self.make(mv);
+        self.outcome()
+    }
+
+    /// Applies `mv` like `Logic::apply`, but returns the information needed to revert it with
+    /// `Logic::unmake` instead of the outcome. Whether the move ended the game can be checked
+    /// cheaply via `Logic::is_over`.
+    pub fn make(&mut self, mv: Move) -> Undo {
         debug_assert!(self.applicable(&mv));
-        match mv.action {
+        let before = match mv.action {
+            Action::Place(pos, _) => self.road_owners(pos, Direction::North, 0),
+            Action::Slide(pos, dir, ref v) => self.road_owners(pos, dir, v.as_ref().map(Vec::len).unwrap_or(1)),
+        };
+        let change = match mv.action {
             Action::Place(pos, kind) => {
                 self.board.place(Piece::new(kind, mv.player), pos);
+                Change::Placed(pos)
             }
-            Action::Slide(pos, dir, v) => {
+            Action::Slide(pos, dir, ref v) => {
                 let mut src = pos;
-                let v = v.unwrap_or_else(|| vec![self.board[pos].len()]);
-                for n in v {
-                    self.board.slide(src, dir, n);
-                    src = src.go(dir);
+                let carries = v.clone().unwrap_or_else(|| vec![self.board.height(pos)]);
+                let mut flattened = false;
+                for n in &carries {
+                    let dst = src.go(dir);
+                    flattened = self.board.top(dst).map(|p| p.kind) == Some(PieceKind::StandingStone);
+                    self.board.slide(src, dir, *n);
+                    src = dst;
+                }
+                Change::Slid { src: pos, dir, carries, flattened }
+            }
+        };
+        // Record slides with explicit carries so that the history can be written as PTN.
+        let action = match (mv.action, &change) {
+            (Action::Slide(pos, dir, _), Change::Slid { carries, .. }) => {
+                Action::Slide(pos, dir, Some(carries.clone()))
+            }
+            (action, _) => action,
+        };
+        self.roads.update(&self.board, &before);
+        self.ply += 1;
+        self.moves.push(Move { action, player: mv.player });
+        Undo { change }
+    }
+
+    /// Reverts the move `undo` was created for. Moves need to be unmade in reverse order.
+    pub fn unmake(&mut self, undo: Undo) {
+        let before = match &undo.change {
+            Change::Placed(pos) => self.road_owners(*pos, Direction::North, 0),
+            Change::Slid { src, dir, carries, .. } => self.road_owners(*src, *dir, carries.len()),
+        };
+        match undo.change {
+            Change::Placed(pos) => self.board.unplace(pos),
+            Change::Slid { src, dir, carries, flattened } => {
+                let last = (0..carries.len()).fold(src, |pos, _| pos.go(dir));
+                let mut dst = last;
+                for n in carries.into_iter().rev() {
+                    self.board.slide(dst, !dir, n);
+                    dst = dst.go(!dir);
+                }
+                if flattened {
+                    self.board.stand_up(last);
                 }
             }
         }
-        self.get_outcome()
+        self.roads.update(&self.board, &before);
+        self.ply -= 1;
+        self.moves.pop();
     }
 
-    fn get_outcome(&self) -> Option<Outcome> {
-        // Naive approach:
+    /// The outcome of the game if a road or the flat count decided it, see `Logic::is_over`.
+    /// A double road is resolved in favour of the player who made the last move.
+    pub fn outcome(&self) -> Option<Outcome> {
+        let mover = !self.to_move();
         let res = match (self.is_winner(Color::Red), self.is_winner(Color::Blk)) {
-            (true, true) => MatchResult::Tie,
+            (true, true) => match self.config.road_tie {
+                RoadTieRule::MoverWins => MatchResult::Winner(mover),
+                RoadTieRule::Tie => MatchResult::Tie,
+            },
             (true, false) => MatchResult::Winner(Color::Red),
             (false, true) => MatchResult::Winner(Color::Blk),
+            (false, false) if self.flats_decide() => return Some(self.end(self.flat_result(), Termination::Flats)),
             (false, false) => return None,
         };
-        Some(Outcome { result: res, board: self.board.clone() })
+        Some(self.end(res, Termination::Road))
     }
 
-    fn is_winner(&self, c: Color) -> bool {
-        self.is_winner_for_dir(c, Direction::North) || self.is_winner_for_dir(c, Direction::East)
+    /// Determines whether the board is full or either player ran out of pieces.
+    fn flats_decide(&self) -> bool {
+        self.board.is_full() || self.board.out_of_pieces(Color::Red) || self.board.out_of_pieces(Color::Blk)
     }
 
-    fn is_winner_for_dir(&self, c: Color, dir: Direction) -> bool {
-        // Helper functions:
-        let is_goal = |pos: &Position| -> bool {
-            match dir {
-                Direction::North => pos.row == self.size() - 1,
-                Direction::South => pos.row == 0,
-                Direction::East => pos.col == self.size() - 1,
-                Direction::West => pos.col == 0,
-            }
-        };
-        let collect_neighbours = |pos: Position| -> Vec<Position> {
-            use crate::board::Direction::*;
-            let mut res = Vec::new();
-            if pos.col > 0 {
-                res.push(pos.go(West))
-            }
-            if pos.col < self.size() - 1 {
-                res.push(pos.go(East))
-            }
-            if pos.row > 0 {
-                res.push(pos.go(South))
-            }
-            if pos.row < self.size() - 1 {
-                res.push(pos.go(North))
-            }
-            res
-        };
-        let qualifies = |p: &Position| {
-            let stack = &self.board[*p];
-            stack.is_road() && stack.color().map(|k| k == c).unwrap_or(false)
-        };
+    /// Determines whether a player completed a road or the flat count decides the game, without
+    /// building the `Outcome`.
+    pub fn is_over(&self) -> bool {
+        self.is_winner(Color::Red) || self.is_winner(Color::Blk) || self.flats_decide()
+    }
 
-        // Naive approach:
-        let mut closed = HashSet::new();
-        let mut frontier: HashSet<Position> = (0..self.size())
-            .map(|ix| match dir {
-                Direction::North => Position::new(0, ix),
-                Direction::South => Position::new(self.board.size() - 1, ix),
-                Direction::East => Position::new(ix, 0),
-                Direction::West => Position::new(ix, self.board.size() - 1),
-            })
-            .filter(qualifies)
-            .collect();
-
-        while !frontier.is_empty() {
-            frontier = frontier.difference(&closed).cloned().collect();
-            closed.extend(frontier.iter());
-            frontier = frontier.drain().flat_map(collect_neighbours).filter(qualifies).collect();
-            if frontier.iter().any(is_goal) {
-                return true;
+    /// Compares the flat counts including komi.
+    fn flat_result(&self) -> MatchResult {
+        // Compare in half flats so that half-point komi decides ties.
+        let flats = self.flats();
+        let red = 2 * flats.of(Color::Red);
+        let blk = 2 * flats.of(Color::Blk) + self.config.komi.half_flats();
+        match red.cmp(&blk) {
+            Ordering::Greater => MatchResult::Winner(Color::Red),
+            Ordering::Less => MatchResult::Winner(Color::Blk),
+            Ordering::Equal => MatchResult::Tie,
+        }
+    }
+
+    /// Ends the game by flat count regardless of the state of the board.
+    pub(crate) fn adjudicate(&self) -> Outcome {
+        self.end(self.flat_result(), Termination::Adjudication)
+    }
+
+    /// Ends the game in a tie because the position repeated too often.
+    pub(crate) fn repetition(&self) -> Outcome {
+        self.end(MatchResult::Tie, Termination::Repetition)
+    }
+
+    /// Ends the game in favour of the opponent of `c`, who failed to provide a legal move.
+    pub(crate) fn forfeit(&self, c: Color, reason: IllegalMove) -> Outcome {
+        self.end(MatchResult::Winner(!c), Termination::Forfeit(reason))
+    }
+
+    /// Ends the game in favour of the opponent of `c`, who resigned.
+    pub(crate) fn resign(&self, c: Color) -> Outcome {
+        self.end(MatchResult::Winner(!c), Termination::Resignation)
+    }
+
+    /// Ends the game in a tie both players agreed upon.
+    pub(crate) fn agree_draw(&self) -> Outcome {
+        self.end(MatchResult::Tie, Termination::Agreement)
+    }
+
+    /// Ends the game in favour of the opponent of `c`, who exceeded their time limit.
+    pub(crate) fn timeout(&self, c: Color) -> Outcome {
+        self.end(MatchResult::Winner(!c), Termination::Timeout)
+    }
+
+    fn end(&self, result: MatchResult, termination: Termination) -> Outcome {
+        let flats = self.flats();
+        // Playtak only scores games decided on the board.
+        let scored = matches!(termination, Termination::Road | Termination::Flats | Termination::Adjudication);
+        let score = match result {
+            MatchResult::Winner(c) if scored => {
+                let reserve =
+                    self.board.piece_count(c, PieceKind::Stone) + self.board.piece_count(c, PieceKind::CapStone);
+                Some((self.size() * self.size()) as u32 + u32::from(reserve))
             }
+            MatchResult::Tie if scored => Some(0),
+            _ => None,
+        };
+        Outcome {
+            result,
+            termination,
+            board: Board::from(&self.board),
+            plies: self.ply,
+            first_ply: self.ply - self.moves.len(),
+            moves: self.moves.clone(),
+            flats,
+            komi: self.config.komi,
+            score,
         }
-        false
+    }
+
+    fn flats(&self) -> Metric<u16> {
+        Metric::for_values(self.board.flat_count(Color::Red), self.board.flat_count(Color::Blk))
+    }
+
+    fn is_winner(&self, c: Color) -> bool {
+        self.roads.of(c).has_road()
+    }
+
+    /// The squares of a road of `c` from the South or West edge to the opposite one, if `c` has
+    /// completed a road.
+    pub fn winning_road(&self, c: Color) -> Option<Vec<Position>> {
+        self.roads.of(c).road(&self.board)
+    }
+
+    /// The road owners of `src` and the `steps` squares following it in `dir` direction.
+    fn road_owners(&self, src: Position, dir: Direction, steps: usize) -> Vec<(Position, Option<Color>)> {
+        std::iter::successors(Some(src), |p| p.checked_go(dir))
+            .take(steps + 1)
+            .map(|p| (p, self.board.road_owner(p)))
+            .collect()
     }
 }
 
@@ -194,50 +439,12 @@ mod tests {
     use super::*;
     use crate::board::piece::Stack;
     use crate::player::Color::*;
+    use crate::simulator::config::Komi;
     use crate::test_util::*;
 
-    fn parse_single(s: &str) -> Stack {
-        let s = s.to_lowercase();
-        if s.len() == 1 {
-            assert_eq!(s, "!");
-            Stack::empty()
-        } else {
-            assert_eq!(s.len() % 2, 0);
-            let mut stack = Vec::new();
-            let mut cs = s.chars();
-            while let Some(c) = cs.next() {
-                let color = match c {
-                    'r' => Red,
-                    'b' => Blk,
-                    sym => panic!("Unrecognized symbol `{}`.", sym),
-                };
-                let kind = match cs.next() {
-                    Some('s') => PieceKind::Stone,
-                    Some('w') | Some('x') => PieceKind::StandingStone,
-                    Some('c') => PieceKind::CapStone,
-                    Some(_) => panic!("Unrecognized symbol"),
-                    None => unreachable!(),
-                };
-                stack.push(Piece::new(kind, color));
-            }
-            Stack::from(stack)
-        }
-    }
-
     fn parse(width: usize, s: &str) -> Board {
-        let mut row = width; // Row 0 is supposed to be the last row.
-        let mut col = 0;
-        let mut board = Board::new(width);
-        for stack in s.split_whitespace().map(parse_single) {
-            if !stack.is_empty() {
-                board.set_forcefully(Position::new(row - 1, col), stack);
-            }
-            col += 1;
-            if col == width {
-                col = 0;
-                row -= 1;
-            }
-        }
+        let board: Board = s.parse().unwrap();
+        assert_eq!(board.size(), width);
         board
     }
 
@@ -264,7 +471,7 @@ mod tests {
         let target = Position::new(1, 2);
         let action = Action::Place(target, PieceKind::Stone);
         let (ml, oc) = apply(s, 3, action, Red);
-        let board = ml.peek();
+        let board = Board::from(ml.peek());
         for (pos, stack) in board.iter().with_pos() {
             if pos == target {
                 assert_eq!(stack, &single_stone(Red));
@@ -330,8 +537,8 @@ mod tests {
         let action = Action::Slide(source, Direction::West, Some(vec![1]));
         let (ml, oc) = apply(start, 3, action, Red);
         assert!(oc.is_none());
-        let was = ml.peek();
-        assert_eq!(was, &expected);
+        let was = Board::from(ml.peek());
+        assert_eq!(was, expected);
     }
 
     #[test]
@@ -341,6 +548,7 @@ mod tests {
         !  !  ! ! !
         !  !  ! ! !
         !  !  ! ! !
+        !  !  ! ! !
         ";
         let expected = parse(
             5,
@@ -349,14 +557,15 @@ mod tests {
         !    ! ! ! !
         !    ! ! ! !
         !    ! ! ! !
+        !    ! ! ! !
         ",
         );
         let source = Position::new(4, 1);
         let action = Action::Slide(source, Direction::West, Some(vec![1]));
         let (ml, oc) = apply(start, 5, action, Red);
         assert!(oc.is_none());
-        let was = ml.peek();
-        assert_eq!(was, &expected);
+        let was = Board::from(ml.peek());
+        assert_eq!(was, expected);
     }
 
     #[test]
@@ -380,8 +589,8 @@ mod tests {
         let action = Action::Slide(source, Direction::East, Some(vec![3, 2]));
         let (ml, oc) = apply(start, 4, action, Red);
         assert!(oc.is_none());
-        let was = ml.peek();
-        assert_eq!(was, &expected);
+        let was = Board::from(ml.peek());
+        assert_eq!(was, expected);
     }
 
     #[test]
@@ -432,11 +641,268 @@ mod tests {
         let source = Position::new(1, 0);
         let action = Action::Slide(source, Direction::East, Some(vec![4, 3, 2, 1]));
         let (ml, oc) = apply(start, 5, action, Red);
-        let was = ml.peek();
-        assert_eq!(was, &expected);
+        let was = Board::from(ml.peek());
+        assert_eq!(was, expected);
         assert!(oc.is_some());
         let oc = oc.unwrap();
         assert_eq!(oc.board, expected);
         assert_eq!(oc.result, MatchResult::Winner(Color::Red));
     }
+
+    #[test]
+    fn test_flat_win_full_board() {
+        let start = "\
+        RS BS RS
+        BS RS BS
+        RS BS !
+        ";
+        let expected = parse(
+            3,
+            "\
+        RS BS RS
+        BS RS BS
+        RS BS RS
+        ",
+        );
+        let action = Action::Place(Position::new(0, 2), PieceKind::Stone);
+        let (_, oc) = apply(start, 3, action, Red);
+        let oc = oc.expect("A full board ends the game.");
+        assert_eq!(oc.board, expected);
+        assert_eq!(oc.result, MatchResult::Winner(Color::Red));
+    }
+
+    #[test]
+    fn test_flat_tie_full_board() {
+        let start = "\
+        RS BS RS
+        BS BX BS
+        RS BS !
+        ";
+        let action = Action::Place(Position::new(0, 2), PieceKind::Stone);
+        let (_, oc) = apply(start, 3, action, Red);
+        assert_eq!(oc.map(|oc| oc.result), Some(MatchResult::Tie));
+    }
+
+    #[test]
+    fn test_flat_win_out_of_pieces() {
+        // Red has 10 stones on a 3x3 board; the last one is placed now.
+        let start = "\
+        RSRSRS  RSRS     !
+        BS      RSRSRSRS !
+        BS      !        !
+        ";
+        let action = Action::Place(Position::new(2, 2), PieceKind::StandingStone);
+        let (_, oc) = apply(start, 3, action, Red);
+        let oc = oc.expect("Running out of pieces ends the game.");
+        assert_eq!(oc.result, MatchResult::Winner(Color::Red));
+    }
+
+    #[test]
+    fn test_no_flat_win_while_pieces_left() {
+        let start = "\
+        RS BS !
+        BS !  !
+        !  !  !
+        ";
+        let action = Action::Place(Position::new(0, 0), PieceKind::Stone);
+        let (_, oc) = apply(start, 3, action, Red);
+        assert!(oc.is_none());
+    }
+
+    #[test]
+    fn test_opening_swap() {
+        let mut ml = Logic::new(GameConfig::standard(3).unwrap());
+        ml.apply(ml.opening_move(Position::new(0, 0), Red));
+        assert_eq!(ml.peek().stack(Position::new(0, 0)), single_stone(Blk));
+
+        let mut ml = Logic::new(GameConfig { swap_opening: false, ..GameConfig::standard(3).unwrap() });
+        ml.apply(ml.opening_move(Position::new(0, 0), Red));
+        assert_eq!(ml.peek().stack(Position::new(0, 0)), single_stone(Red));
+    }
+
+    fn apply_with_komi(b: &str, width: usize, action: Action, color: Color, komi: Komi) -> Option<Outcome> {
+        let mut ml = Logic::from_board(parse(width, b));
+        ml.config.komi = komi;
+        ml.apply(Move { action, player: color })
+    }
+
+    #[test]
+    fn test_flat_win_komi() {
+        let start = "\
+        RS BS RS
+        BS RS BS
+        RS BS !
+        ";
+        let action = Action::Place(Position::new(0, 2), PieceKind::Stone);
+
+        let oc = apply_with_komi(start, 3, action.clone(), Red, Komi::flats(1)).unwrap();
+        assert_eq!(oc.result, MatchResult::Tie);
+        assert_eq!(oc.flats.of(Red), 5);
+        assert_eq!(oc.flats.of(Blk), 4);
+        assert_eq!(oc.komi, Komi::flats(1));
+        assert_eq!(oc.flat_margin(Red), 0.0);
+
+        let oc = apply_with_komi(start, 3, action, Red, Komi::from_half_flats(3)).unwrap();
+        assert_eq!(oc.result, MatchResult::Winner(Blk));
+        assert_eq!(oc.flat_margin(Blk), 0.5);
+        assert_eq!(oc.flat_margin(Red), -0.5);
+    }
+
+    #[test]
+    fn test_double_road() {
+        let start = "\
+        !    RS RS
+        BSRS BS BS
+        !    !  !
+        ";
+        let source = Position::new(1, 0);
+        let action = Action::Slide(source, Direction::North, Some(vec![1]));
+        let (_, oc) = apply(start, 3, action.clone(), Red);
+        assert_eq!(oc.map(|oc| oc.result), Some(MatchResult::Winner(Red)));
+
+        let mut ml = Logic::from_board(parse(3, start));
+        ml.config.road_tie = RoadTieRule::Tie;
+        let oc = ml.apply(Move { action, player: Red });
+        assert_eq!(oc.map(|oc| oc.result), Some(MatchResult::Tie));
+    }
+
+    #[test]
+    fn test_winning_road() {
+        let start = "\
+        BS BS !
+        RS RS !
+        BS RS BS
+        ";
+        let (ml, oc) = apply(start, 3, Action::Place(Position::new(1, 2), PieceKind::Stone), Red);
+        assert_eq!(oc.map(|oc| oc.result), Some(MatchResult::Winner(Red)));
+        let road: Vec<String> = ml.winning_road(Red).unwrap().iter().map(Position::to_string).collect();
+        assert_eq!(road, vec!["a2", "b2", "c2"]);
+        assert_eq!(ml.winning_road(Blk), None);
+    }
+
+    fn check(b: &str, width: usize, action: Action, color: Color) -> Result<(), IllegalMove> {
+        Logic::from_board(parse(width, b)).check(&Move { action, player: color })
+    }
+
+    #[test]
+    fn test_check_carry_limit() {
+        let start = "\
+        !        ! !
+        !        ! !
+        RSBSRSRS ! !
+        ";
+        let source = Position::new(0, 0);
+        let action = Action::Slide(source, Direction::North, None);
+        assert_eq!(check(start, 3, action, Red), Err(IllegalMove::CarryLimit { limit: 3, requested: 4 }));
+        let action = Action::Slide(source, Direction::North, Some(vec![3, 1]));
+        assert_eq!(check(start, 3, action, Red), Ok(()));
+    }
+
+    #[test]
+    fn test_check_blocked() {
+        let start = "\
+        RX RS BC ! !
+        !  !  !  ! !
+        !  !  !  ! !
+        !  !  !  ! !
+        !  !  !  ! !
+        ";
+        let source = Position::new(4, 1);
+        let action = Action::Slide(source, Direction::West, None);
+        assert_eq!(check(start, 5, action, Red), Err(IllegalMove::BlockedByWall(Position::new(4, 0))));
+        let action = Action::Slide(source, Direction::East, None);
+        assert_eq!(check(start, 5, action, Red), Err(IllegalMove::BlockedByCapstone(Position::new(4, 2))));
+        let action = Action::Slide(source, Direction::North, None);
+        assert_eq!(check(start, 5, action, Red), Err(IllegalMove::OffBoard));
+        let action = Action::Slide(Position::new(4, 2), Direction::South, None);
+        assert_eq!(check(start, 5, action, Red), Err(IllegalMove::WrongOwner(Position::new(4, 2))));
+    }
+
+    #[test]
+    fn test_check_turn_order() {
+        let action = Action::Place(Position::new(0, 0), PieceKind::Stone);
+        let empty = "! ! ! ! ! ! ! ! !";
+        assert_eq!(check(empty, 3, action, Blk), Err(IllegalMove::OutOfTurn(Red)));
+    }
+
+    #[test]
+    fn test_check_opening() {
+        let mut ml = Logic::new(GameConfig::standard(5).unwrap());
+        let pos = Position::new(0, 0);
+        let wall = Move { action: Action::Place(pos, PieceKind::StandingStone), player: Blk };
+        assert_eq!(ml.check(&wall), Err(IllegalMove::OpeningRequiresFlat));
+        let own = Move { action: Action::Place(pos, PieceKind::Stone), player: Red };
+        assert_eq!(ml.check(&own), Err(IllegalMove::OpeningColor(Blk)));
+        assert_eq!(ml.check(&ml.opening_move(pos, Red)), Ok(()));
+        ml.apply(ml.opening_move(pos, Red));
+        assert_eq!(ml.to_move(), Blk);
+        let slide = Move { action: Action::Slide(pos, Direction::North, None), player: Red };
+        assert_eq!(ml.check(&slide), Err(IllegalMove::OpeningRequiresFlat));
+    }
+
+    #[test]
+    fn test_unmake_flattening() {
+        let start = "\
+        RX RSRC ! ! !
+        !  !    ! ! !
+        !  !    ! ! !
+        !  !    ! ! !
+        !  !    ! ! !
+        ";
+        let mut ml = Logic::from_board(parse(5, start));
+        let control = ml.clone();
+        let action = Action::Slide(Position::new(4, 1), Direction::West, Some(vec![1]));
+        let undo = ml.make(Move { action, player: Red });
+        assert_eq!(ml.peek().stack(Position::new(4, 0)), Stack::from(vec![stone(Red), cap_stone(Red)]));
+        ml.unmake(undo);
+        assert_eq!(ml, control);
+    }
+
+    #[test]
+    fn test_unmake_along_game() {
+        let mut ml = Logic::new(GameConfig::standard(5).unwrap());
+        let mut history = vec![ml.clone()];
+        let mut undos = Vec::new();
+        for i in 0..60 {
+            let moves = ml.legal_moves();
+            let mv = moves[(i * 7919) % moves.len()].clone();
+            let undo = ml.make(mv);
+            assert_eq!(ml.roads, Roads::build(&ml.board));
+            assert_eq!(ml.is_over(), ml.outcome().is_some());
+            undos.push(undo);
+            if ml.is_over() {
+                break;
+            }
+            history.push(ml.clone());
+        }
+        while let Some(undo) = undos.pop() {
+            ml.unmake(undo);
+            assert_eq!(Some(&ml), history.get(undos.len()));
+        }
+        assert_eq!(ml, Logic::new(GameConfig::standard(5).unwrap()));
+    }
+
+    #[test]
+    fn test_move_from_ptn() {
+        let mut logic = Logic::new(GameConfig::standard(5).unwrap());
+        let first = logic.move_from_ptn("a1").unwrap();
+        assert_eq!(first, Move { action: Action::Place(Position::new(0, 0), PieceKind::Stone), player: Blk });
+        logic.apply(first);
+        let second = logic.move_from_ptn("e5").unwrap();
+        assert_eq!(second.player, Red);
+        logic.apply(second);
+        assert_eq!(logic.move_from_ptn("Sb2").unwrap().player, Red);
+        assert_eq!(logic.move_from_ptn("3a1"), Err(PtnError::MissingDirection("3a1".to_string())));
+    }
+
+    #[test]
+    fn test_history_has_explicit_carries() {
+        let mut board = Board::new(&GameConfig::standard(5).unwrap()).unwrap();
+        let pos = Position::new(0, 0);
+        board.set_forcefully(pos, stack_stone_rbr());
+        let mut logic = Logic::from_board(board);
+        logic.apply(Move { action: Action::Slide(pos, Direction::North, None), player: Red });
+        assert_eq!(logic.moves()[0].action, Action::Slide(pos, Direction::North, Some(vec![3])));
+        assert_eq!(logic.moves()[0].action.to_ptn(&Board::from(logic.peek())), "3a1+");
+    }
 }
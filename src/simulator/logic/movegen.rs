@@ -0,0 +1,184 @@
+use super::Logic;
+use crate::actions::{Action, Move};
+use crate::board::piece::PieceKind;
+use crate::board::{BitBoard, Direction, Position};
+use crate::player::Color;
+use std::cmp::min;
+
+impl Logic {
+    /// Generates every move `Logic::check` accepts for the player to move.
+    /// Slides are generated in their canonical form, i.e. with an explicit list of carries, so
+    /// no two generated moves have the same effect.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        if self.in_opening() {
+            let player = self.opening_color(self.to_move());
+            empty_positions(&self.board)
+                .map(|pos| Move { action: Action::Place(pos, PieceKind::Stone), player })
+                .collect()
+        } else {
+            let player = self.to_move();
+            actions(&self.board, player).into_iter().map(|action| Move { action, player }).collect()
+        }
+    }
+}
+
+/// Generates every placement and slide `player` can perform on `board` after the opening.
+pub(crate) fn actions(board: &BitBoard, player: Color) -> Vec<Action> {
+    let mut actions = Vec::new();
+    let mut kinds = Vec::new();
+    if board.piece_count(player, PieceKind::Stone) > 0 {
+        kinds.push(PieceKind::Stone);
+        kinds.push(PieceKind::StandingStone);
+    }
+    if board.piece_count(player, PieceKind::CapStone) > 0 {
+        kinds.push(PieceKind::CapStone);
+    }
+    for pos in empty_positions(board) {
+        actions.extend(kinds.iter().map(|kind| Action::Place(pos, *kind)));
+    }
+
+    for pos in BitBoard::positions(board.owned_by(player)) {
+        let kind = board.top(pos).expect("The stack is owned by the player.").kind;
+        for dir in &Direction::ALL {
+            for carry in 1..=min(board.height(pos), board.size()) {
+                let mut carries = vec![carry];
+                slides(board, pos, pos, *dir, kind, &mut carries, &mut actions);
+            }
+        }
+    }
+    actions
+}
+
+fn empty_positions(board: &BitBoard) -> impl Iterator<Item = Position> {
+    BitBoard::positions(board.squares() & !board.occupied())
+}
+
+/// Generates all slides from `src` starting with `carries` where the last carry is dropped beyond
+/// `from`. `kind` is the kind of the top piece, which leads the carried pieces in every step.
+fn slides(
+    board: &BitBoard,
+    src: Position,
+    from: Position,
+    dir: Direction,
+    kind: PieceKind,
+    carries: &mut Vec<usize>,
+    actions: &mut Vec<Action>,
+) {
+    let dst = match board.topology().step(from, dir) {
+        Some(dst) => dst,
+        None => return,
+    };
+    let carried = *carries.last().unwrap();
+    if board.check_compatible(dst, kind, carried).is_err() {
+        return; // Longer slides are blocked as well.
+    }
+    actions.push(Action::Slide(src, dir, Some(carries.clone())));
+    for next in 1..carried {
+        carries.push(next);
+        slides(board, src, dst, dir, kind, carries, actions);
+        carries.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::board::Board;
+    use crate::simulator::config::GameConfig;
+    use crate::simulator::config::{MAX_SIZE, MIN_SIZE};
+
+    /// All strictly decreasing sequences of positive numbers starting with at most `first` and
+    /// at most `len` elements.
+    fn sequences(first: usize, len: usize) -> Vec<Vec<usize>> {
+        let mut res = Vec::new();
+        if len == 0 {
+            return res;
+        }
+        for head in 1..=first {
+            res.push(vec![head]);
+            for tail in sequences(head - 1, len - 1) {
+                let mut seq = vec![head];
+                seq.extend(tail);
+                res.push(seq);
+            }
+        }
+        res
+    }
+
+    /// Enumerates candidate moves naively and keeps those `Logic::check` accepts.
+    fn brute_force(logic: &Logic) -> Vec<Move> {
+        let board = Board::from(logic.peek());
+        let kinds = [PieceKind::Stone, PieceKind::StandingStone, PieceKind::CapStone];
+        let mut candidates = Vec::new();
+        for (pos, stack) in board.iter().with_pos() {
+            for &player in &[Color::Red, Color::Blk] {
+                candidates.extend(kinds.iter().map(|kind| Move { action: Action::Place(pos, *kind), player }));
+                for dir in &Direction::ALL {
+                    for carries in sequences(stack.len() + 1, board.size()) {
+                        candidates.push(Move { action: Action::Slide(pos, *dir, Some(carries)), player });
+                    }
+                }
+            }
+        }
+        candidates.into_iter().filter(|mv| logic.applicable(mv)).collect()
+    }
+
+    fn sorted(moves: Vec<Move>) -> Vec<String> {
+        let mut res: Vec<String> = moves.iter().map(|mv| format!("{:?}", mv)).collect();
+        res.sort();
+        res
+    }
+
+    /// Plays a pseudo-random game and compares the generator against the naive enumeration in
+    /// every position.
+    fn compare_along_game(size: usize, plies: usize, mut seed: u64) {
+        let mut logic = Logic::new(GameConfig::standard(size).unwrap());
+        for _ in 0..plies {
+            let moves = logic.legal_moves();
+            let generated = sorted(moves.clone());
+            let mut deduplicated = generated.clone();
+            deduplicated.dedup();
+            assert_eq!(generated, deduplicated);
+            assert_eq!(generated, sorted(brute_force(&logic)));
+            if moves.is_empty() {
+                return;
+            }
+            seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+            let mv = moves[(seed >> 33) as usize % moves.len()].clone();
+            if logic.apply(mv).is_some() {
+                return;
+            }
+        }
+    }
+
+    #[test]
+    fn test_matches_check_for_all_sizes() {
+        for size in MIN_SIZE..=MAX_SIZE {
+            compare_along_game(size, 40, size as u64);
+        }
+    }
+
+    #[test]
+    fn test_opening() {
+        let logic = Logic::new(GameConfig::standard(4).unwrap());
+        let moves = logic.legal_moves();
+        assert_eq!(moves.len(), 16);
+        assert!(moves.iter().all(|mv| mv.player == Color::Blk));
+    }
+
+    #[test]
+    fn test_capstone_flattening() {
+        let mut board = Board::new(&GameConfig::standard(5).unwrap()).unwrap();
+        let src = Position::new(0, 0);
+        board.place(crate::test_util::cap_stone(Color::Red), src);
+        board.place(crate::test_util::standing(Color::Blk), Position::new(0, 1));
+        let slides: Vec<Action> = actions(&BitBoard::from(&board), Color::Red)
+            .into_iter()
+            .filter(|a| matches!(a, Action::Slide(..)))
+            .collect();
+        assert!(slides.contains(&Action::Slide(src, Direction::East, Some(vec![1]))));
+        assert!(slides.contains(&Action::Slide(src, Direction::North, Some(vec![1]))));
+        assert_eq!(slides.len(), 2);
+    }
+}
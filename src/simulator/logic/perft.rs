@@ -0,0 +1,113 @@
+use super::Logic;
+use crate::actions::Move;
+
+impl Logic {
+    /// Counts the leaves of the tree of legal moves `depth` plies deep.
+    /// Positions in which the game is over have no successors, so they only count as a leaf if
+    /// they are exactly `depth` plies deep.
+    pub fn perft(&self, depth: usize) -> u64 {
+        self.clone().perft_mut(depth)
+    }
+
+    /// Lists the perft subtotal of each legal move, i.e. the number of leaves `depth - 1` plies
+    /// below the position the move leads to.
+    pub fn divide(&self, depth: usize) -> Vec<(Move, u64)> {
+        assert!(depth > 0, "Divide needs to look at least one ply ahead.");
+        let mut logic = self.clone();
+        self.legal_moves().into_iter().map(|mv| (mv.clone(), logic.subtree(mv, depth))).collect()
+    }
+
+    fn perft_mut(&mut self, depth: usize) -> u64 {
+        match depth {
+            0 => 1,
+            1 => self.legal_moves().len() as u64,
+            _ => self.legal_moves().into_iter().map(|mv| self.subtree(mv, depth)).sum(),
+        }
+    }
+
+    /// Counts the leaves below `mv`, which is the first of `depth` plies.
+    fn subtree(&mut self, mv: Move, depth: usize) -> u64 {
+        let undo = self.make(mv);
+        let count = if self.is_over() && depth > 1 { 0 } else { self.perft_mut(depth - 1) };
+        self.unmake(undo);
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::actions::Action;
+    use crate::board::piece::PieceKind;
+    use crate::board::{Direction, Position};
+    use crate::player::Color::Blk;
+    use crate::simulator::config::GameConfig;
+
+    fn start(size: usize) -> Logic {
+        Logic::new(GameConfig::standard(size).unwrap())
+    }
+
+    fn play(size: usize, actions: Vec<Action>) -> Logic {
+        let mut logic = start(size);
+        for action in actions {
+            let mv = logic.move_for(action);
+            assert_eq!(logic.check(&mv), Ok(()));
+            assert!(logic.apply(mv).is_none());
+        }
+        logic
+    }
+
+    fn place(row: usize, col: usize, kind: PieceKind) -> Action {
+        Action::Place(Position::new(row, col), kind)
+    }
+
+    fn assert_perft(logic: &Logic, expected: &[u64]) {
+        for (depth, n) in expected.iter().enumerate() {
+            assert_eq!(logic.perft(depth), *n, "perft({}) differs", depth);
+        }
+    }
+
+    /// The counts at depth three follow from the rules: every remaining empty square admits each
+    /// piece kind in stock, and the own stone can slide to each neighbour. 5x5 at depth four is
+    /// the value other engines agree on.
+    #[test]
+    fn test_start_positions() {
+        assert_perft(&start(3), &[1, 9, 72, 1_200, 17_792]);
+        assert_perft(&start(4), &[1, 16, 240, 7_440]);
+        assert_perft(&start(5), &[1, 25, 600, 43_320, 2_999_784]);
+        assert_perft(&start(6), &[1, 36, 1_260, 132_720]);
+        assert_perft(&start(7), &[1, 49, 2_352, 339_696]);
+        assert_perft(&start(8), &[1, 64, 4_032, 764_064]);
+    }
+
+    /// Black to move after Red's capstone moved from c3 onto d3 and flattened Black's wall there.
+    /// Unlike the start positions, these counts are regression values produced by this move
+    /// generator and have not been checked against another engine.
+    #[test]
+    fn test_midgame_position() {
+        use PieceKind::*;
+        let logic = play(
+            5,
+            vec![
+                place(0, 0, Stone),
+                place(4, 4, Stone),
+                place(2, 2, CapStone),
+                place(2, 3, StandingStone),
+                Action::Slide(Position::new(2, 2), Direction::East, Some(vec![1])),
+                place(1, 3, Stone),
+                place(3, 3, Stone),
+            ],
+        );
+        assert_perft(&logic, &[1, 65, 3_499, 201_667]);
+    }
+
+    #[test]
+    fn test_divide_sums_up() {
+        let logic = start(4);
+        let divided = logic.divide(3);
+        assert_eq!(divided.len(), 16);
+        assert_eq!(divided.iter().map(|(_, n)| n).sum::<u64>(), logic.perft(3));
+        assert!(divided.iter().all(|(mv, _)| mv.player == Blk));
+    }
+}
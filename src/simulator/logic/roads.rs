@@ -0,0 +1,296 @@
+//! Incremental road detection. Each player's road pieces form a union-find structure whose
+//! components remember the edges of the board they touch, so a road exists iff a component
+//! touches two opposite edges.
+//! Moves only add the squares they handed to a player. Union-find cannot split components, so
+//! if a move took road squares away from a player, the components that contained them are
+//! rebuilt from their remaining squares while all other components are kept.
+
+use crate::board::{BitBoard, Direction, Position};
+use crate::player::Color;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+
+/// The road networks of both players.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Roads {
+    red: RoadNetwork,
+    blk: RoadNetwork,
+}
+
+impl Roads {
+    pub(crate) fn build(board: &BitBoard) -> Roads {
+        Roads { red: RoadNetwork::build(board, Color::Red), blk: RoadNetwork::build(board, Color::Blk) }
+    }
+
+    pub(crate) fn of(&self, c: Color) -> &RoadNetwork {
+        match c {
+            Color::Red => &self.red,
+            Color::Blk => &self.blk,
+        }
+    }
+
+    /// Updates the networks after a change of `board` that touched only the squares in `before`,
+    /// each paired with its road owner prior to the change.
+    pub(crate) fn update(&mut self, board: &BitBoard, before: &[(Position, Option<Color>)]) {
+        self.red.update(board, before);
+        self.blk.update(board, before);
+    }
+}
+
+/// The connectivity of one player's road pieces.
+#[derive(Debug, Clone)]
+pub(crate) struct RoadNetwork {
+    color: Color,
+    size: usize,
+    /// The squares in row-major order.
+    parent: Vec<usize>,
+    /// The number of squares in the tree below each root.
+    weight: Vec<usize>,
+    /// The edges touched by the component of each root, see `edge_bit`.
+    edges: Vec<u8>,
+    /// The edges `dir` for which a component touches both `dir` and `!dir`.
+    spans: u8,
+}
+
+impl RoadNetwork {
+    pub(crate) fn build(board: &BitBoard, color: Color) -> RoadNetwork {
+        let len = board.size() * board.size();
+        let mut network = RoadNetwork {
+            color,
+            size: board.size(),
+            parent: (0..len).collect(),
+            weight: vec![1; len],
+            edges: vec![0; len],
+            spans: 0,
+        };
+        for pos in BitBoard::positions(board.roads(color)) {
+            network.add(board, pos);
+        }
+        network
+    }
+
+    /// Whether the player's road pieces connect the edge in `dir` direction with the opposite one.
+    pub(crate) fn connects(&self, dir: Direction) -> bool {
+        self.spans & edge_bit(dir) != 0
+    }
+
+    pub(crate) fn has_road(&self) -> bool {
+        self.connects(Direction::North) || self.connects(Direction::East)
+    }
+
+    /// The squares of a shortest road, starting at the South or West edge, if there is one.
+    pub(crate) fn road(&self, board: &BitBoard) -> Option<Vec<Position>> {
+        [Direction::North, Direction::East].iter().filter(|dir| self.connects(**dir)).find_map(|dir| {
+            let (from, to) = (edge_bit(!*dir), edge_bit(*dir));
+            let spanning = |p: &Position| {
+                let edges = self.edges[self.root(self.index(*p))];
+                board.road_owner(*p) == Some(self.color) && edges & from != 0 && edges & to != 0
+            };
+            let mut came_from: HashMap<Position, Option<Position>> = HashMap::new();
+            let mut queue: VecDeque<Position> = board.topology().edge(!*dir).filter(spanning).collect();
+            came_from.extend(queue.iter().map(|p| (*p, None)));
+            while let Some(pos) = queue.pop_front() {
+                if board.topology().is_on_edge(pos, *dir) {
+                    let mut road: Vec<Position> = std::iter::successors(Some(pos), |p| came_from[p]).collect();
+                    road.reverse();
+                    return Some(road);
+                }
+                for next in board.topology().neighbours(pos).filter(spanning) {
+                    if let Entry::Vacant(entry) = came_from.entry(next) {
+                        entry.insert(Some(pos));
+                        queue.push_back(next);
+                    }
+                }
+            }
+            None
+        })
+    }
+
+    /// Adds the squares the player gained and rebuilds the components of the squares they lost.
+    fn update(&mut self, board: &BitBoard, before: &[(Position, Option<Color>)]) {
+        let color = Some(self.color);
+        let owned = |pos: Position| board.road_owner(pos) == color;
+        let mut added: Vec<Position> =
+            before.iter().filter(|(pos, owner)| *owner != color && owned(*pos)).map(|(pos, _)| *pos).collect();
+        let lost_roots: Vec<usize> = before
+            .iter()
+            .filter(|(pos, owner)| *owner == color && !owned(*pos))
+            .map(|(pos, _)| self.root(self.index(*pos)))
+            .collect();
+        if !lost_roots.is_empty() {
+            let affected: Vec<usize> =
+                (0..self.parent.len()).filter(|ix| lost_roots.contains(&self.root(*ix))).collect();
+            for &ix in &affected {
+                self.parent[ix] = ix;
+                self.weight[ix] = 1;
+                self.edges[ix] = 0;
+            }
+            let size = self.size;
+            let remaining: Vec<Position> =
+                affected.into_iter().map(|ix| Position::new(ix / size, ix % size)).filter(|pos| owned(*pos)).collect();
+            added.retain(|pos| !remaining.contains(pos));
+            added.extend(remaining);
+            // Only the kept components may still span the board until the others are added again.
+            self.spans = (0..self.parent.len())
+                .filter(|ix| self.parent[*ix] == *ix)
+                .fold(0, |spans, ix| spans | spanned(self.edges[ix]));
+        }
+        for pos in added {
+            self.add(board, pos);
+        }
+    }
+
+    /// Connects `pos` with the adjacent road squares of the player.
+    /// Assumes `pos` to be a road square of the player.
+    fn add(&mut self, board: &BitBoard, pos: Position) {
+        let ix = self.index(pos);
+        for next in board.topology().neighbours(pos) {
+            if board.road_owner(next) == Some(self.color) {
+                self.union(ix, self.index(next));
+            }
+        }
+        // The neighbours may have been joined before being added themselves, so the edges of `pos`
+        // go to the root directly.
+        let root = self.root(ix);
+        self.edges[root] |=
+            Direction::ALL.iter().filter(|d| board.topology().is_on_edge(pos, **d)).map(|d| edge_bit(*d)).sum::<u8>();
+        self.spans |= spanned(self.edges[root]);
+    }
+
+    fn index(&self, pos: Position) -> usize {
+        pos.row * self.size + pos.col
+    }
+
+    /// Union by weight keeps the trees shallow, so the root can be found without path compression.
+    fn root(&self, mut ix: usize) -> usize {
+        while self.parent[ix] != ix {
+            ix = self.parent[ix];
+        }
+        ix
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.root(a), self.root(b));
+        if a == b {
+            return;
+        }
+        let (small, large) = if self.weight[a] < self.weight[b] { (a, b) } else { (b, a) };
+        self.parent[small] = large;
+        self.weight[large] += self.weight[small];
+        self.edges[large] |= self.edges[small];
+    }
+}
+
+fn edge_bit(dir: Direction) -> u8 {
+    match dir {
+        Direction::North => 1,
+        Direction::East => 2,
+        Direction::South => 4,
+        Direction::West => 8,
+    }
+}
+
+/// The edges `dir` for which `edges` contains both `dir` and `!dir`.
+fn spanned(edges: u8) -> u8 {
+    Direction::ALL
+        .iter()
+        .filter(|d| edges & edge_bit(**d) != 0 && edges & edge_bit(!**d) != 0)
+        .map(|d| edge_bit(*d))
+        .sum()
+}
+
+impl PartialEq for RoadNetwork {
+    /// Networks are equal if they partition the squares the same way, regardless of the order in
+    /// which the squares were added.
+    fn eq(&self, other: &RoadNetwork) -> bool {
+        self.color == other.color
+            && self.size == other.size
+            && self.spans == other.spans
+            && (0..self.parent.len())
+                .all(|ix| (0..ix).all(|jx| (self.root(ix) == self.root(jx)) == (other.root(ix) == other.root(jx))))
+    }
+}
+
+impl Eq for RoadNetwork {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::board::Board;
+    use crate::test_util::stone;
+
+    fn road(board: &str, c: Color) -> Option<Vec<String>> {
+        let board = BitBoard::from(&board.parse::<Board>().unwrap());
+        RoadNetwork::build(&board, c).road(&board).map(|r| r.iter().map(Position::to_string).collect())
+    }
+
+    #[test]
+    fn test_road() {
+        let board = "\
+        BS ! RS !
+        !  ! BS RS
+        !  ! BS RS
+        !  ! RX RS";
+        assert_eq!(road(board, Color::Red), None);
+        let board = "\
+        BS ! RS !
+        !  ! RS RS
+        !  ! BS RS
+        !  ! RX RS";
+        assert_eq!(
+            road(board, Color::Red),
+            Some(["d1", "d2", "d3", "c3", "c4"].iter().map(|s| s.to_string()).collect())
+        );
+        assert_eq!(road(board, Color::Blk), None);
+        let board = "\
+        !  !  !  !
+        RS RS RS RS
+        RS !  !  RS
+        !  !  !  !";
+        assert_eq!(road(board, Color::Red), Some(["a3", "b3", "c3", "d3"].iter().map(|s| s.to_string()).collect()));
+    }
+
+    #[test]
+    fn test_update() {
+        let mut board = BitBoard::from(&"RS RS ! RS ! ! ! ! !".parse::<Board>().unwrap());
+        let mut roads = Roads::build(&board);
+        assert!(!roads.of(Color::Red).has_road());
+
+        let (c2, c3) = (Position::new(1, 2), Position::new(2, 2));
+        let before = vec![(c3, board.road_owner(c3))];
+        board.place(stone(Color::Red), c3);
+        roads.update(&board, &before);
+        assert!(roads.of(Color::Red).connects(Direction::East));
+        assert_eq!(roads, Roads::build(&board));
+
+        board.place(stone(Color::Blk), c2);
+        let before = vec![(c2, board.road_owner(c2)), (c3, board.road_owner(c3))];
+        board.slide(c2, Direction::North, 1);
+        roads.update(&board, &before);
+        assert!(!roads.of(Color::Red).has_road());
+        assert_eq!(roads, Roads::build(&board));
+    }
+
+    #[test]
+    fn test_update_keeps_unaffected_components() {
+        let mut board = BitBoard::from(&"RS ! ! RS RS ! ! RS RS ! ! RS RS ! ! RS".parse::<Board>().unwrap());
+        let mut roads = Roads::build(&board);
+        assert!(roads.of(Color::Red).connects(Direction::North));
+
+        // Splits the column on the right while the one on the left still spans the board.
+        let (c2, d2) = (Position::new(1, 2), Position::new(1, 3));
+        let before = vec![(d2, board.road_owner(d2)), (c2, board.road_owner(c2))];
+        board.slide(d2, Direction::West, 1);
+        roads.update(&board, &before);
+        assert!(roads.of(Color::Red).connects(Direction::North));
+        assert_eq!(roads, Roads::build(&board));
+
+        let a3 = Position::new(2, 0);
+        let before = vec![(a3, board.road_owner(a3)), (Position::new(2, 1), None)];
+        board.slide(a3, Direction::East, 1);
+        roads.update(&board, &before);
+        assert!(!roads.of(Color::Red).has_road());
+        assert_eq!(roads, Roads::build(&board));
+    }
+}
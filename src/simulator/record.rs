@@ -0,0 +1,47 @@
+use super::config::GameConfig;
+use super::game_over::Outcome;
+use crate::actions::Move;
+use crate::ptn::{PtnError, PtnGame, PtnResult, Tps};
+use std::time::Duration;
+
+/// Everything needed to reproduce a finished game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameRecord {
+    pub config: GameConfig,
+    /// Name of the player playing Red.
+    pub red: String,
+    /// Name of the player playing Blk.
+    pub blk: String,
+    /// The position the game started from if it did not start on an empty board.
+    pub start: Option<Tps>,
+    /// Every move in the order it was played.
+    pub moves: Vec<RecordedMove>,
+    pub outcome: Outcome,
+}
+
+/// A move along with the circumstances it was played in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedMove {
+    pub mv: Move,
+    /// The number of plies played before this move, including those before the starting position.
+    pub ply: usize,
+    /// The time since the start of the game when the move was played, if known.
+    pub elapsed: Option<Duration>,
+    pub comment: Option<String>,
+}
+
+impl GameRecord {
+    /// The game in PTN, including the comments of the moves.
+    /// Fails at the first move that is illegal or follows the end of the game.
+    pub fn to_ptn(&self) -> Result<PtnGame, PtnError> {
+        let mut game = PtnGame::new(&self.config);
+        game.player1 = Some(self.red.clone());
+        game.player2 = Some(self.blk.clone());
+        game.result = Some(PtnResult::from(&self.outcome));
+        game.tps = self.start.as_ref().map(Tps::to_string);
+        game.extend_moves(self.moves.iter().map(|m| m.mv.clone()))?;
+        game.comments =
+            self.moves.iter().enumerate().filter_map(|(i, m)| m.comment.as_ref().map(|c| (i + 1, c.clone()))).collect();
+        Ok(game)
+    }
+}
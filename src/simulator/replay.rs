@@ -0,0 +1,235 @@
+use super::config::ConfigError;
+use super::illegal_move::IllegalMove;
+use super::logic::{Logic, Undo};
+use super::record::GameRecord;
+use crate::actions::Move;
+use crate::board::Board;
+use crate::ptn::{PtnError, PtnGame};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// A cursor over a recorded game that can step through the moves in both directions.
+/// Plies are counted like `Logic::ply`, i.e. including the plies before the starting position.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    logic: Logic,
+    moves: Vec<Move>,
+    /// The number of plies before the first move.
+    start: usize,
+    /// Reverts the moves applied so far, in order.
+    undos: Vec<Undo>,
+}
+
+impl Replay {
+    /// Places the cursor at `start`, the state of the game before the first of `moves`.
+    /// Fails if a move is illegal or follows the end of the game.
+    pub fn new(start: Logic, moves: Vec<Move>) -> Result<Replay, ReplayError> {
+        let mut replay = Replay { start: start.ply(), logic: start, moves, undos: Vec::new() };
+        let mut over = false;
+        for mv in &replay.moves {
+            let ply = replay.logic.ply();
+            if over {
+                return Err(ReplayError::MoveAfterEnd { ply });
+            }
+            replay.logic.check(mv).map_err(|reason| ReplayError::IllegalMove { ply, reason })?;
+            let undo = replay.logic.make(mv.clone());
+            replay.undos.push(undo);
+            over = replay.logic.is_over();
+        }
+        replay.jump(replay.start);
+        Ok(replay)
+    }
+
+    /// Fails if the configuration of the record is invalid or does not match its starting position.
+    pub fn from_record(record: &GameRecord) -> Result<Replay, ReplayError> {
+        record.config.validate().map_err(ReplayError::InvalidConfig)?;
+        if let Some(tps) = &record.start {
+            if tps.board.size() != record.config.size {
+                let mismatch = ConfigError::BoardSizeMismatch { size: record.config.size, board: tps.board.size() };
+                return Err(ReplayError::InvalidConfig(mismatch));
+            }
+        }
+        let start = match &record.start {
+            Some(tps) => Logic::from_position(record.config.clone(), tps.board.clone(), tps.ply()),
+            None => Logic::new(record.config.clone()),
+        };
+        Replay::new(start, record.moves.iter().map(|m| m.mv.clone()).collect())
+    }
+
+    pub fn from_ptn(game: &PtnGame) -> Result<Replay, PtnError> {
+        // Replay the game first to report illegal moves with their PTN move number.
+        game.replay()?;
+        Ok(Replay::new(game.start()?, game.moves().to_vec()).expect("The game was replayed successfully."))
+    }
+
+    /// The board at the current ply, e.g. for the `Analyzer`.
+    pub fn board(&self) -> Board {
+        Board::from(self.logic.peek())
+    }
+
+    /// The state of the game at the current ply.
+    pub fn logic(&self) -> &Logic {
+        &self.logic
+    }
+
+    pub fn ply(&self) -> usize {
+        self.logic.ply()
+    }
+
+    /// The first ply of the game, i.e. the number of plies before the first recorded move.
+    pub fn first_ply(&self) -> usize {
+        self.start
+    }
+
+    /// The last ply of the game, i.e. after all recorded moves.
+    pub fn last_ply(&self) -> usize {
+        self.start + self.moves.len()
+    }
+
+    /// The move leading to the current ply, if any.
+    pub fn last_move(&self) -> Option<&Move> {
+        self.undos.len().checked_sub(1).map(|i| &self.moves[i])
+    }
+
+    /// The move following the current ply, if any.
+    pub fn next_move(&self) -> Option<&Move> {
+        self.moves.get(self.undos.len())
+    }
+
+    /// Advances by one ply. Returns false if the cursor is already at the end of the game.
+    pub fn forward(&mut self) -> bool {
+        match self.next_move().cloned() {
+            Some(mv) => {
+                let undo = self.logic.make(mv);
+                self.undos.push(undo);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Goes back by one ply. Returns false if the cursor is already at the start of the game.
+    pub fn back(&mut self) -> bool {
+        match self.undos.pop() {
+            Some(undo) => {
+                self.logic.unmake(undo);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves the cursor to `ply`. Returns false and leaves the cursor unchanged if `ply` is not
+    /// within `first_ply..=last_ply`.
+    pub fn jump(&mut self, ply: usize) -> bool {
+        if ply < self.first_ply() || ply > self.last_ply() {
+            return false;
+        }
+        while self.ply() < ply {
+            self.forward();
+        }
+        while self.ply() > ply {
+            self.back();
+        }
+        true
+    }
+}
+
+/// The reason why a list of moves cannot be replayed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayError {
+    /// The move in this ply is illegal.
+    IllegalMove { ply: usize, reason: IllegalMove },
+    /// The move in this ply follows the end of the game.
+    MoveAfterEnd { ply: usize },
+    /// The recorded game cannot be played with its configuration.
+    InvalidConfig(ConfigError),
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            ReplayError::IllegalMove { ply, reason } => write!(f, "The move in ply {} is illegal: {}", ply, reason),
+            ReplayError::MoveAfterEnd { ply } => write!(f, "The move in ply {} follows the end of the game.", ply),
+            ReplayError::InvalidConfig(e) => write!(f, "The recorded configuration is invalid: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::actions::Action;
+    use crate::analyzer::Analyzer;
+    use crate::board::piece::PieceKind;
+    use crate::board::Position;
+    use crate::player::Color;
+    use crate::simulator::config::GameConfig;
+    use crate::simulator::Simulator;
+    use crate::test_util::ScriptedPlayer;
+
+    const GAME: &str = "[Size \"4\"]\n1. a1 d4\n2. b2 c3\n3. b2< c3<\n4. b1 b3+\n";
+
+    #[test]
+    fn test_navigation() {
+        let game: PtnGame = GAME.parse().unwrap();
+        let mut replay = Replay::from_ptn(&game).unwrap();
+        assert_eq!((replay.first_ply(), replay.last_ply(), replay.ply()), (0, 8, 0));
+        assert_eq!(replay.board(), Board::new(&GameConfig::standard(4).unwrap()).unwrap());
+        assert_eq!(replay.last_move(), None);
+        assert!(!replay.back());
+
+        assert!(replay.forward());
+        assert_eq!(replay.ply(), 1);
+        assert_eq!(replay.last_move(), Some(&game.moves()[0]));
+        assert_eq!(replay.next_move(), Some(&game.moves()[1]));
+
+        assert!(replay.jump(6));
+        let board = replay.board();
+        assert_eq!(board[Position::new(1, 0)].len(), 1);
+        assert_eq!(Analyzer::for_board(&board).flats().of(Color::Red), 2);
+        assert!(replay.jump(8));
+        assert!(!replay.forward());
+        assert!(replay.back() && replay.back());
+        assert_eq!(replay.board(), board);
+        assert!(!replay.jump(9));
+        assert_eq!(replay.ply(), 6);
+        assert!(replay.jump(0));
+        assert_eq!(replay.board(), Board::new(&GameConfig::standard(4).unwrap()).unwrap());
+    }
+
+    #[test]
+    fn test_from_record() {
+        let place = |row, col| Action::Place(Position::new(row, col), PieceKind::Stone);
+        let red = ScriptedPlayer::new("red", vec![Position::new(2, 2)], vec![place(0, 0), place(1, 0), place(2, 0)]);
+        let blk = ScriptedPlayer::new("blk", vec![Position::new(0, 2)], vec![place(2, 1), place(1, 1)]);
+        let mut sim = Simulator::new(red, blk, GameConfig::standard(3).unwrap()).unwrap();
+        let outcome = sim.start();
+        let mut replay = Replay::from_record(sim.record().unwrap()).unwrap();
+        assert!(replay.jump(replay.last_ply()));
+        assert_eq!(replay.board(), outcome.board);
+
+        let mut record = sim.record().unwrap().clone();
+        record.config.size = 9;
+        assert_eq!(
+            Replay::from_record(&record).err(),
+            Some(ReplayError::InvalidConfig(ConfigError::UnsupportedSize(9)))
+        );
+    }
+
+    #[test]
+    fn test_illegal_moves() {
+        let logic = Logic::new(GameConfig::standard(3).unwrap());
+        let place = |row, col| Action::Place(Position::new(row, col), PieceKind::Stone);
+        let moves = vec![logic.move_for(place(0, 0)), Move { action: place(0, 0), player: Color::Red }];
+        let reason = IllegalMove::OccupiedSquare(Position::new(0, 0));
+        assert_eq!(Replay::new(logic.clone(), moves).err(), Some(ReplayError::IllegalMove { ply: 1, reason }));
+
+        let game: PtnGame = "[Size \"3\"]\n1. c3 a1\n2. a2 b2\n3. a3\n".parse().unwrap();
+        let mut moves = game.moves().to_vec();
+        moves.push(Move { action: place(1, 2), player: Color::Blk });
+        assert_eq!(Replay::new(logic, moves).err(), Some(ReplayError::MoveAfterEnd { ply: 5 }));
+    }
+}
@@ -1,5 +1,14 @@
+use crate::actions::{Action, Decision, DrawResponse};
 use crate::board::piece::{Piece, PieceKind, Stack};
-use crate::player::{Color, Color::*};
+use crate::board::{Board, Position};
+use crate::player::{Color, Color::*, Player, PlayerBuilder};
+use crate::simulator::clock::Clock;
+use crate::simulator::config::GameConfig;
+use crate::simulator::game_over::Outcome;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::time::Duration;
 
 #[allow(dead_code)]
 pub(crate) fn stone(c: Color) -> Piece {
@@ -45,3 +54,107 @@ pub(crate) fn stack_with_cap_rbr() -> Stack {
 pub(crate) fn stack_with_standing_rbr() -> Stack {
     Stack::from(vec![stone(Red), stone(Blk), standing(Red)])
 }
+
+/// A player replaying a fixed list of positions for the opening and actions afterwards.
+/// Panics if the simulator asks for more than the script contains.
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub(crate) struct ScriptedPlayer {
+    name: String,
+    openings: VecDeque<Position>,
+    decisions: VecDeque<Decision>,
+    accepts_draws: bool,
+    clock: ManualClock,
+    thinking: Duration,
+}
+
+#[allow(dead_code)]
+impl ScriptedPlayer {
+    pub(crate) fn new(name: &str, openings: Vec<Position>, actions: Vec<Action>) -> ScriptedPlayer {
+        Self::deciding(name, openings, actions.into_iter().map(Decision::Play).collect())
+    }
+
+    pub(crate) fn deciding(name: &str, openings: Vec<Position>, decisions: Vec<Decision>) -> ScriptedPlayer {
+        let name = String::from(name);
+        let (openings, decisions) = (openings.into(), decisions.into());
+        let (clock, thinking) = (ManualClock::default(), Duration::from_secs(0));
+        ScriptedPlayer { name, openings, decisions, accepts_draws: false, clock, thinking }
+    }
+
+    pub(crate) fn accepting_draws(self) -> ScriptedPlayer {
+        ScriptedPlayer { accepts_draws: true, ..self }
+    }
+
+    /// Spends `thinking` on every decision, see `ScriptedPlayer::timed_by`.
+    pub(crate) fn thinking(self, thinking: Duration) -> ScriptedPlayer {
+        ScriptedPlayer { thinking, ..self }
+    }
+
+    /// Advances `clock` while thinking.
+    pub(crate) fn timed_by(self, clock: &ManualClock) -> ScriptedPlayer {
+        ScriptedPlayer { clock: clock.clone(), ..self }
+    }
+}
+
+/// A clock that only advances when told to; clones share the time.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ManualClock(Rc<Cell<Duration>>);
+
+#[allow(dead_code)]
+impl ManualClock {
+    pub(crate) fn advance(&self, by: Duration) {
+        self.0.set(self.0.get() + by);
+    }
+}
+
+impl Clock for ManualClock {
+    fn elapsed(&self) -> Duration {
+        self.0.get()
+    }
+}
+
+impl PlayerBuilder<ScriptedPlayer> for ScriptedPlayer {
+    fn setup(self, _config: &GameConfig, _color: Color, _first: bool) -> ScriptedPlayer {
+        self
+    }
+}
+
+impl Player for ScriptedPlayer {
+    fn welcome(&mut self, _opponent: &str) {}
+
+    fn action_for(&mut self, _board: &Board, _opponent_action: Option<Action>) -> Decision {
+        self.clock.advance(self.thinking);
+        self.decisions.pop_front().expect("The script ran out of decisions.")
+    }
+
+    fn first_action(&mut self, _board: &Board) -> Position {
+        self.clock.advance(self.thinking);
+        self.openings.pop_front().expect("The script ran out of opening positions.")
+    }
+
+    fn respond_to_draw(&mut self, _board: &Board) -> DrawResponse {
+        self.clock.advance(self.thinking);
+        if self.accepts_draws {
+            DrawResponse::Accept
+        } else {
+            DrawResponse::Decline
+        }
+    }
+
+    fn accept_outcome(&mut self, _outcome: &Outcome) {}
+
+    fn name(&self) -> &String {
+        &self.name
+    }
+}
+
+/// Checks that `value` serializes to `expected` and deserializes back to `value`.
+#[cfg(all(test, feature = "serde"))]
+pub(crate) fn check_serde<T>(value: &T, expected: serde_json::Value)
+where
+    T: serde::Serialize + serde::de::DeserializeOwned + PartialEq + std::fmt::Debug,
+{
+    assert_eq!(serde_json::to_value(value).unwrap(), expected);
+    assert_eq!(&serde_json::from_value::<T>(expected).unwrap(), value);
+}